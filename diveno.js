@@ -16,8 +16,75 @@
 
 import init_wasm, { init_diveno } from "./pkg/diveno.js";
 
-document.getElementById("start-button").onclick = function() {
+function startGame(resume) {
   document.getElementById("instructions").style.display = "none";
   document.getElementById("message").style.display = "block";
-  init_wasm().then(() => init_diveno());
+  let n_teams = parseInt(document.getElementById("n-teams").value);
+  let blackBall = document.getElementById("black-ball").value;
+  let guessTime = parseInt(document.getElementById("guess-time").value);
+  let passOnTimeout = document.getElementById("pass-on-timeout").checked;
+  let steal = document.getElementById("steal").checked;
+  let autoPass = document.getElementById("auto-pass").checked;
+  let hintOnPass = document.getElementById("hint-on-pass").checked;
+  let difficulty = document.getElementById("difficulty").value;
+  let inputMethod = document.getElementById("input-method").value;
+  // A seed can be given in the URL, for example “?seed=1234”, to
+  // replay the same game
+  let params = new URLSearchParams(window.location.search);
+  let seed = params.get("seed");
+  // The language profile can also be chosen in the URL, for example
+  // “?lang=fr” to load data/fr.lang
+  let language = params.get("lang");
+  let wordListFiles = document.getElementById("word-list").files;
+  let wordList = wordListFiles.length > 0
+      ? wordListFiles[0].text()
+      : Promise.resolve(null);
+
+  Promise.all([init_wasm(), wordList]).then(([_, wordList]) => {
+    init_diveno(n_teams,
+                blackBall,
+                guessTime,
+                passOnTimeout,
+                steal,
+                autoPass,
+                hintOnPass,
+                difficulty,
+                inputMethod,
+                language,
+                resume,
+                seed,
+                wordList);
+  });
+}
+
+document.getElementById("start-button").onclick = function() {
+  startGame(false);
+};
+
+let resumeButton = document.getElementById("resume-button");
+
+if (window.localStorage.getItem("diveno-save") !== null) {
+  resumeButton.style.display = "inline";
+}
+
+resumeButton.onclick = function() {
+  startGame(true);
+};
+
+// Words that the host accepted even though they weren’t in the
+// dictionary. These can be downloaded to extend the dictionary later.
+let acceptedWords = window.localStorage.getItem("diveno-accepted-words");
+let exportButton = document.getElementById("export-button");
+
+if (acceptedWords) {
+  exportButton.style.display = "inline";
+}
+
+exportButton.onclick = function() {
+  let blob = new Blob([acceptedWords], { type: "text/plain" });
+  let link = document.createElement("a");
+  link.href = URL.createObjectURL(blob);
+  link.download = "akceptitaj-vortoj.txt";
+  link.click();
+  URL.revokeObjectURL(link.href);
 };
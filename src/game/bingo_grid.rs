@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::random;
+use super::{random, save_state};
 
 pub const GRID_WIDTH: usize = 5;
 pub const GRID_HEIGHT: usize = 5;
@@ -74,6 +74,7 @@ impl Bingo {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct BingoGrid {
     spaces_covered: u32,
     spaces: [u8; N_SPACES],
@@ -100,10 +101,10 @@ impl BingoGrid {
         }
     }
 
-    pub fn reset(&mut self) {
-        random::shuffle(&mut self.spaces);
+    pub fn reset(&mut self, rng: &mut random::Rng) {
+        rng.shuffle(&mut self.spaces);
         self.bingo = None;
-        self.spaces_covered = generate_initial_spaces_covered();
+        self.spaces_covered = generate_initial_spaces_covered(rng);
 
         let mut spaces_uncovered = !self.spaces_covered & ((1 << N_SPACES) - 1);
 
@@ -155,6 +156,58 @@ impl BingoGrid {
         self.bingo
     }
 
+    pub fn write_state(&self, line: &mut save_state::LineWriter) {
+        line
+            .add_list(self.spaces.iter())
+            .add(self.spaces_covered)
+            .add_list(self.initial_uncovered_space_map.iter());
+    }
+
+    pub fn read_state(
+        fields: &mut save_state::Fields,
+    ) -> Result<BingoGrid, String> {
+        let mut grid = BingoGrid::new();
+
+        let spaces = fields.next_list::<u8>()?;
+        let mut balls_seen = 0u32;
+
+        for &ball in spaces.iter() {
+            if ball as usize >= N_SPACES || balls_seen & (1 << ball) != 0 {
+                return Err(fields.error("invalid bingo grid"));
+            }
+
+            balls_seen |= 1 << ball;
+        }
+
+        if spaces.len() != N_SPACES {
+            return Err(fields.error("invalid bingo grid"));
+        }
+
+        grid.spaces.copy_from_slice(&spaces);
+
+        grid.spaces_covered = fields.next_number()?;
+
+        if grid.spaces_covered >> N_SPACES != 0 {
+            return Err(fields.error("invalid covered spaces"));
+        }
+
+        let map = fields.next_list::<u8>()?;
+
+        if map.len() != N_INITIAL_SPACES_UNCOVERED
+            || map.iter().any(|&space| space as usize >= N_SPACES)
+        {
+            return Err(fields.error("invalid uncovered space map"));
+        }
+
+        grid.initial_uncovered_space_map.copy_from_slice(&map);
+
+        grid.bingo = (0..N_SPACES)
+            .filter(|&index| grid.spaces_covered & (1 << index) != 0)
+            .find_map(|index| grid.bingo_for_covered_space(index));
+
+        Ok(grid)
+    }
+
     fn bingo_for_covered_space(&self, index: usize) -> Option<Bingo> {
         let column = (index % GRID_WIDTH) as u32;
         let row = (index / GRID_WIDTH) as u32;
@@ -318,12 +371,12 @@ impl CoveredSpacesGenerator {
     }
 }
 
-fn generate_initial_spaces_covered() -> u32 {
+fn generate_initial_spaces_covered(rng: &mut random::Rng) -> u32 {
     let mut generator = CoveredSpacesGenerator::new();
 
     for _ in 0..N_INITIAL_SPACES_COVERED {
         let random_range = generator.next_random_number_range();
-        generator.cover_next_space(random::random_range(random_range));
+        generator.cover_next_space(rng.range(random_range));
     }
 
     let spaces_covered = generator.spaces_covered;
@@ -464,6 +517,20 @@ mod test {
         assert_eq!(bingo.letter_index_for_space(24), None);
     }
 
+    #[test]
+    fn reset_with_seed() {
+        let mut grid_a = BingoGrid::new();
+        let mut grid_b = BingoGrid::new();
+
+        for seed in 0..100 {
+            grid_a.reset(&mut random::Rng::new(seed));
+            grid_b.reset(&mut random::Rng::new(seed));
+
+            assert!(grid_a == grid_b);
+            validate_initial_spaces_covered(grid_a.spaces_covered);
+        }
+    }
+
     struct StackEntry {
         next_random_number: usize,
         state: CoveredSpacesGenerator,
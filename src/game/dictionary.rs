@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+// Number of bits for each choice in the first version of the word
+// list format
 const BITS_PER_CHOICE: u32 = 5;
 
 pub struct Dictionary {
@@ -77,45 +79,50 @@ impl Dictionary {
         }
     }
 
-    pub fn extract_word(&self, mut word: u64) -> Option<String> {
-        // Skip the root node
-        let Some(Node { remainder, child_offset, .. }) =
-            Node::extract(&self.data)
-        else {
-            return None;
-        };
+    // Extracts a word from the start of a word list in the
+    // versioned format and returns it along with the rest of the
+    // data. Each choice is the number of siblings to skip before
+    // descending to a child, stored as a variable-length number in
+    // the same way as the offsets in the dictionary.
+    pub fn extract_word<'a>(
+        &self,
+        mut encoding: &'a [u8],
+    ) -> Option<(String, &'a [u8])> {
+        let mut data = self.first_child()?;
+        let mut buf = String::new();
 
-        if child_offset == 0 {
-            return None;
+        loop {
+            let (remainder, to_skip) = read_offset(encoding)?;
+            encoding = remainder;
+
+            let node = skip_siblings(data, to_skip)?;
+
+            if node.letter == '\0' {
+                return Some((buf, encoding));
+            }
+
+            buf.push(node.letter);
+
+            if node.child_offset == 0 {
+                return None;
+            }
+
+            data = node.remainder.get(node.child_offset..)?;
         }
+    }
 
-        let mut data = &remainder[child_offset..];
+    // Extracts a word from the u64 representation used in the first
+    // version of the word list format. The u64 is split into 5-bit
+    // choices starting from the least-significant bits.
+    pub fn extract_legacy_word(&self, mut word: u64) -> Option<String> {
+        let mut data = self.first_child()?;
         let mut buf = String::new();
 
         loop {
             let to_skip = word & ((1 << BITS_PER_CHOICE) - 1);
             word >>= BITS_PER_CHOICE;
 
-            for _ in 0..to_skip {
-                let Some(node) = Node::extract(data)
-                else {
-                    return None;
-                };
-
-                if node.sibling_offset == 0 {
-                    return None;
-                }
-
-                data = match node.remainder.get(node.sibling_offset..) {
-                    Some(d) => d,
-                    None => return None,
-                };
-            }
-
-            let Some(node) = Node::extract(data)
-            else {
-                return None;
-            };
+            let node = skip_siblings(data, to_skip as usize)?;
 
             if node.letter == '\0' {
                 return Some(buf);
@@ -127,10 +134,182 @@ impl Dictionary {
                 return None;
             }
 
-            data = match node.remainder.get(node.child_offset..) {
-                Some(d) => d,
-                None => return None,
+            data = node.remainder.get(node.child_offset..)?;
+        }
+    }
+
+    // Returns the data for the first child of the root node
+    fn first_child(&self) -> Option<&[u8]> {
+        let Node { remainder, child_offset, .. } = Node::extract(&self.data)?;
+
+        if child_offset == 0 {
+            return None;
+        }
+
+        remainder.get(child_offset..)
+    }
+
+    // Returns an iterator over the words in the dictionary. If
+    // `length` is given then only words with that many letters are
+    // returned. `filter` is called with the position and letter of
+    // each letter before descending into the words that have that
+    // letter at that position. If it returns false then all of those
+    // words are skipped. The letters are in lowercase.
+    pub fn search<F>(&self, length: Option<usize>, filter: F) -> Words<'_, F>
+    where
+        F: FnMut(usize, char) -> bool,
+    {
+        let mut stack = Vec::new();
+
+        // Skip the root node
+        if let Some(Node { remainder, child_offset, .. }) =
+            Node::extract(&self.data)
+        {
+            if child_offset != 0 {
+                if let Some(data) = remainder.get(child_offset..) {
+                    stack.push(SearchLevel::new(data));
+                }
+            }
+        }
+
+        Words {
+            stack,
+            buf: String::new(),
+            length,
+            filter,
+        }
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = FoundWord> + '_ {
+        self.search(None, |_, _| true)
+    }
+
+    pub fn words_of_length(
+        &self,
+        length: usize,
+    ) -> impl Iterator<Item = FoundWord> + '_ {
+        self.search(Some(length), |_, _| true)
+    }
+
+    // Returns an iterator over the words that match the pattern. Each
+    // “?” in the pattern matches any letter and the other letters
+    // have to match exactly, ignoring the case.
+    pub fn words_matching(
+        &self,
+        pattern: &str,
+    ) -> impl Iterator<Item = FoundWord> + '_ {
+        let pattern = pattern.chars()
+            .map(|ch| {
+                (ch != '?').then(|| ch.to_lowercase().next().unwrap_or(ch))
+            })
+            .collect::<Vec<_>>();
+
+        self.search(Some(pattern.len()), move |pos, letter| {
+            pattern[pos].unwrap_or(letter) == letter
+        })
+    }
+}
+
+pub struct FoundWord {
+    pub word: String,
+    // The representation used in the versioned word list format
+    pub encoding: Vec<u8>,
+}
+
+// One level of the graph while searching for words
+struct SearchLevel<'a> {
+    // The next sibling to visit or None if all of them have been
+    // visited
+    next: Option<&'a [u8]>,
+    // The number of siblings before `next`
+    n_skipped: usize,
+    // The number of siblings that were skipped before the node that
+    // the search descended into
+    choice: usize,
+}
+
+impl<'a> SearchLevel<'a> {
+    fn new(data: &'a [u8]) -> SearchLevel<'a> {
+        SearchLevel {
+            next: Some(data),
+            n_skipped: 0,
+            choice: 0,
+        }
+    }
+}
+
+pub struct Words<'a, F> {
+    stack: Vec<SearchLevel<'a>>,
+    buf: String,
+    length: Option<usize>,
+    filter: F,
+}
+
+impl<'a, F> Words<'a, F> {
+    fn encoding(&self, last_choice: usize) -> Vec<u8> {
+        let mut encoding = Vec::new();
+
+        for level in self.stack.iter().take(self.stack.len() - 1) {
+            write_offset(&mut encoding, level.choice);
+        }
+
+        write_offset(&mut encoding, last_choice);
+
+        encoding
+    }
+}
+
+impl<'a, F> Iterator for Words<'a, F>
+where
+    F: FnMut(usize, char) -> bool,
+{
+    type Item = FoundWord;
+
+    fn next(&mut self) -> Option<FoundWord> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            let level = &mut self.stack[depth];
+
+            let Some(data) = level.next
+            else {
+                self.stack.pop();
+                self.buf.pop();
+                continue;
+            };
+
+            let Some(node) = Node::extract(data)
+            else {
+                // The dictionary is corrupt
+                self.stack.clear();
+                return None;
             };
+
+            let choice = level.n_skipped;
+
+            level.n_skipped += 1;
+            level.next = if node.sibling_offset == 0 {
+                None
+            } else {
+                node.remainder.get(node.sibling_offset..)
+            };
+
+            if node.letter == '\0' {
+                if self.length.unwrap_or(depth) == depth {
+                    return Some(FoundWord {
+                        word: self.buf.clone(),
+                        encoding: self.encoding(choice),
+                    });
+                }
+            } else if node.child_offset != 0
+                && !matches!(self.length, Some(length) if depth >= length)
+                && (self.filter)(depth, node.letter)
+            {
+                if let Some(child) = node.remainder.get(node.child_offset..) {
+                    self.stack[depth].choice = choice;
+                    self.buf.push(node.letter);
+                    self.stack.push(SearchLevel::new(child));
+                }
+            }
         }
     }
 }
@@ -153,6 +332,37 @@ fn read_offset(data: &[u8]) -> Option<(&[u8], usize)> {
     None
 }
 
+// Appends a number in the same variable-length format that
+// read_offset reads
+fn write_offset(buf: &mut Vec<u8>, mut offset: usize) {
+    loop {
+        let byte = (offset & 0x7f) as u8;
+        offset >>= 7;
+
+        if offset == 0 {
+            buf.push(byte);
+            break;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+// Returns the node after skipping the given number of siblings
+fn skip_siblings(mut data: &[u8], to_skip: usize) -> Option<Node<'_>> {
+    for _ in 0..to_skip {
+        let node = Node::extract(data)?;
+
+        if node.sibling_offset == 0 {
+            return None;
+        }
+
+        data = node.remainder.get(node.sibling_offset..)?;
+    }
+
+    Node::extract(data)
+}
+
 struct Node<'a> {
     sibling_offset: usize,
     child_offset: usize,
@@ -178,16 +388,25 @@ impl<'a> Node<'a> {
 }
 
 #[cfg(test)]
-mod test {
+pub mod test {
     use super::*;
 
+    // Dictionary that contains “a”, “b”, “c”, “apple”, “app”, “ĉapelo”
+    pub static DICTIONARY_BYTES: [u8; 52] = [
+        0x00, 0x01, 0x2a, 0x01, 0x07, b'a', 0x01, 0x29, b'b', 0x04, 0x26,
+        b'c', 0x08, 0x00, 0x00, 0x00, 0x02, 0xc4, 0x89, 0x00, 0x07, b'a',
+        0x00, 0x01, b'p', 0x00, 0x04, b'p', 0x00, 0x04, b'p', 0x04, 0x00,
+        0x00, 0x00, 0x04, b'e', 0x00, 0x04, b'l', 0x00, 0x04, b'l', 0x00,
+        0x04, b'e', 0x00, 0x01, b'o', 0x00, 0x00, 0x00,
+    ];
+
     #[test]
     fn extract() {
         let node = Node::extract(&[7, 8, b'c']).unwrap();
         assert_eq!(node.sibling_offset, 7);
         assert_eq!(node.child_offset, 8);
         assert_eq!(node.letter, 'c');
-        assert_eq!(node.remainder, &[b'c']);
+        assert_eq!(node.remainder, b"c");
 
         let node = Node::extract(&[7, 8, 0xc4, 0x89]).unwrap();
         assert_eq!(node.sibling_offset, 7);
@@ -207,21 +426,12 @@ mod test {
         assert_eq!(node.sibling_offset, 0b11111111111111);
         assert_eq!(node.child_offset, 0b10000000000000);
         assert_eq!(node.letter, 'c');
-        assert_eq!(node.remainder, &[b'c']);
+        assert_eq!(node.remainder, b"c");
     }
 
     #[test]
     fn contains() {
-        // Dictionary that contains “a”, “b”, “c”, “apple”, “app”, “ĉapelo”
-        static DICTIONARY_BYTES: [u8; 52] = [
-            0x00, 0x01, 0x2a, 0x01, 0x07, b'a', 0x01, 0x29, b'b', 0x04, 0x26,
-            b'c', 0x08, 0x00, 0x00, 0x00, 0x02, 0xc4, 0x89, 0x00, 0x07, b'a',
-            0x00, 0x01, b'p', 0x00, 0x04, b'p', 0x00, 0x04, b'p', 0x04, 0x00,
-            0x00, 0x00, 0x04, b'e', 0x00, 0x04, b'l', 0x00, 0x04, b'l', 0x00,
-            0x04, b'e', 0x00, 0x01, b'o', 0x00, 0x00, 0x00,
-        ];
-
-        let dictionary = Dictionary::new(Box::new(DICTIONARY_BYTES.clone()));
+        let dictionary = Dictionary::new(Box::new(DICTIONARY_BYTES));
 
         assert!(dictionary.contains("a"));
         assert!(dictionary.contains("b"));
@@ -239,4 +449,179 @@ mod test {
         assert!(dictionary.contains("APPLE"));
         assert!(dictionary.contains("ĈAPelo"));
     }
+
+    // Encodes a word in the versioned word list format in the same
+    // way as make-word-list
+    fn encode_word(dictionary: &Dictionary, word: &str) -> Option<Vec<u8>> {
+        let mut data = dictionary.first_child()?;
+        let mut encoding = Vec::new();
+
+        for letter in word.chars()
+            .flat_map(char::to_lowercase)
+            .chain(std::iter::once('\0'))
+        {
+            let mut skip_count = 0;
+
+            let node = loop {
+                let node = Node::extract(data)?;
+
+                if node.letter == letter {
+                    break node;
+                }
+
+                if node.sibling_offset == 0 {
+                    return None;
+                }
+
+                data = node.remainder.get(node.sibling_offset..)?;
+                skip_count += 1;
+            };
+
+            write_offset(&mut encoding, skip_count);
+
+            if letter == '\0' {
+                return Some(encoding);
+            }
+
+            if node.child_offset == 0 {
+                return None;
+            }
+
+            data = node.remainder.get(node.child_offset..)?;
+        }
+
+        None
+    }
+
+    #[test]
+    fn extract_word() {
+        let dictionary = Dictionary::new(Box::new(DICTIONARY_BYTES));
+
+        for word in ["a", "b", "c", "apple", "app", "ĉapelo"] {
+            let encoded = encode_word(&dictionary, word).unwrap();
+            assert_eq!(
+                dictionary.extract_word(&encoded),
+                Some((word.to_string(), &[][..])),
+            );
+        }
+
+        assert_eq!(encode_word(&dictionary, "APP"), Some(vec![0, 1, 0, 0]));
+        assert!(encode_word(&dictionary, "appl").is_none());
+        assert!(encode_word(&dictionary, "d").is_none());
+
+        // The rest of the data is returned
+        assert_eq!(
+            dictionary.extract_word(&[2, 0, 0, 1]),
+            Some(("c".to_string(), &[0, 1][..])),
+        );
+        // There are only four letters at the first level
+        assert!(dictionary.extract_word(&[4, 0]).is_none());
+        // Incomplete words
+        assert!(dictionary.extract_word(&[0, 1]).is_none());
+        assert!(dictionary.extract_word(&[0x80]).is_none());
+
+        assert_eq!(dictionary.extract_legacy_word(0).unwrap(), "a");
+        assert_eq!(dictionary.extract_legacy_word(32).unwrap(), "app");
+        assert_eq!(dictionary.extract_legacy_word(32800).unwrap(), "apple");
+        assert_eq!(dictionary.extract_legacy_word(3).unwrap(), "ĉapelo");
+        assert!(dictionary.extract_legacy_word(4).is_none());
+    }
+
+    #[test]
+    fn long_words() {
+        // A dictionary with a single word that is too long for the
+        // first version of the word list format
+        let word = "abcdefghijklmnopqrst";
+        let mut data = vec![0, 1, b'*'];
+
+        for letter in word.bytes() {
+            data.extend_from_slice(&[0, 1, letter]);
+        }
+
+        data.extend_from_slice(&[0, 0, 0]);
+
+        let dictionary = Dictionary::new(data.into_boxed_slice());
+        let encoded = encode_word(&dictionary, word).unwrap();
+
+        assert_eq!(encoded, [0; 21]);
+        assert_eq!(
+            dictionary.extract_word(&encoded),
+            Some((word.to_string(), &[][..])),
+        );
+
+        // A dictionary with 200 one-letter words so that the choices
+        // need more than 5 bits, and more than one byte
+        let mut data = vec![0, 1, b'*'];
+
+        for letter_num in 0..200 {
+            let letter = char::from_u32(0x100 + letter_num).unwrap();
+            let sibling_offset = if letter_num < 199 { 5 } else { 0 };
+
+            data.extend_from_slice(&[sibling_offset, 2]);
+            data.extend_from_slice(letter.encode_utf8(&mut [0; 2]).as_bytes());
+            data.extend_from_slice(&[0, 0, 0]);
+        }
+
+        let dictionary = Dictionary::new(data.into_boxed_slice());
+
+        assert_eq!(encode_word(&dictionary, "ĥ"), Some(vec![37, 0]));
+        assert_eq!(
+            dictionary.extract_word(&[0x96, 0x01, 0]),
+            Some(("Ɩ".to_string(), &[][..])),
+        );
+        assert!(dictionary.extract_word(&[200, 1, 0]).is_none());
+
+        let found = dictionary.words().nth(150).unwrap();
+        assert_eq!(found.word, "Ɩ");
+        assert_eq!(found.encoding, [0x96, 0x01, 0]);
+    }
+
+    #[test]
+    fn search() {
+        let dictionary = Dictionary::new(Box::new(DICTIONARY_BYTES));
+
+        let collect = |words: &mut dyn Iterator<Item = FoundWord>| {
+            words.map(|found| {
+                // The encoding should match the word list format
+                assert_eq!(
+                    encode_word(&dictionary, &found.word).as_ref(),
+                    Some(&found.encoding),
+                );
+                assert_eq!(
+                    dictionary.extract_word(&found.encoding),
+                    Some((found.word.clone(), &[][..])),
+                );
+                found.word
+            }).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            collect(&mut dictionary.words()),
+            ["a", "app", "apple", "b", "c", "ĉapelo"],
+        );
+
+        assert_eq!(collect(&mut dictionary.words_of_length(1)), ["a", "b", "c"]);
+        assert_eq!(collect(&mut dictionary.words_of_length(3)), ["app"]);
+        assert_eq!(collect(&mut dictionary.words_of_length(6)), ["ĉapelo"]);
+        assert!(collect(&mut dictionary.words_of_length(2)).is_empty());
+        assert!(collect(&mut dictionary.words_of_length(0)).is_empty());
+
+        assert_eq!(collect(&mut dictionary.words_matching("?")), ["a", "b", "c"]);
+        assert_eq!(collect(&mut dictionary.words_matching("A?P?E")), ["apple"]);
+        assert_eq!(
+            collect(&mut dictionary.words_matching("ĉ?????")),
+            ["ĉapelo"],
+        );
+        assert!(collect(&mut dictionary.words_matching("a?p?o")).is_empty());
+        assert!(collect(&mut dictionary.words_matching("")).is_empty());
+
+        assert_eq!(
+            collect(&mut dictionary.search(Some(1), |_, letter| letter != 'b')),
+            ["a", "c"],
+        );
+        assert_eq!(
+            collect(&mut dictionary.search(None, |pos, _| pos < 3)),
+            ["a", "app", "b", "c"],
+        );
+    }
 }
@@ -0,0 +1,228 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use super::dictionary::Dictionary;
+use super::language::Language;
+
+// Extra difficulty for each letter that appears more than once in a
+// word
+const REPEATED_LETTER_WEIGHT: f32 = 1.0;
+// Extra difficulty for each letter with a diacritic
+const HATTED_LETTER_WEIGHT: f32 = 1.5;
+// Multiplied by the logarithm of the number of words that differ by
+// only one letter. Words with lots of neighbours are harder because
+// the guesses can’t tell them apart as quickly.
+const NEIGHBOUR_WEIGHT: f32 = 0.5;
+
+/// A band of word difficulty. Each band contains about a third of the
+/// words of each length.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    /// Parses “easy”, “medium” or “hard”
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("invalid difficulty: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+/// Gives a difficulty score to words based on statistics of the
+/// dictionary. A higher score means the word is harder to guess.
+pub struct Rater<'a> {
+    dictionary: &'a Dictionary,
+    language: &'a Language,
+    // The number of bits of information for each letter, ie,
+    // -log₂(frequency). Rare letters have a higher score.
+    letter_scores: HashMap<char, f32>,
+}
+
+impl<'a> Rater<'a> {
+    pub fn new(
+        dictionary: &'a Dictionary,
+        language: &'a Language,
+    ) -> Rater<'a> {
+        let mut letter_counts = HashMap::<char, u32>::new();
+        let mut total = 0;
+
+        for found in dictionary.words() {
+            for letter in found.word.chars() {
+                *letter_counts.entry(letter).or_insert(0) += 1;
+                total += 1;
+            }
+        }
+
+        let letter_scores = letter_counts.into_iter()
+            .map(|(letter, count)| {
+                (letter, -(count as f32 / total as f32).log2())
+            })
+            .collect();
+
+        Rater {
+            dictionary,
+            language,
+            letter_scores,
+        }
+    }
+
+    pub fn rate(&self, word: &str) -> f32 {
+        let letters = word.chars()
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>();
+
+        if letters.is_empty() {
+            return 0.0;
+        }
+
+        // Letters that aren’t in the dictionary at all are treated
+        // like the rarest letter
+        let max_letter_score = self.letter_scores.values()
+            .copied()
+            .fold(0.0, f32::max);
+
+        let rarity = letters.iter().map(|letter| {
+            self.letter_scores.get(letter)
+                .copied()
+                .unwrap_or(max_letter_score)
+        }).sum::<f32>() / letters.len() as f32;
+
+        let n_repeated = letters.iter().enumerate().filter(|&(index, letter)| {
+            letters[0..index].contains(letter)
+        }).count();
+
+        let n_hatted = letters.iter().filter(|&&letter| {
+            self.language.has_diacritic(letter)
+        }).count();
+
+        rarity
+            + n_repeated as f32 * REPEATED_LETTER_WEIGHT
+            + n_hatted as f32 * HATTED_LETTER_WEIGHT
+            + (1.0 + self.count_neighbours(&letters) as f32).ln()
+            * NEIGHBOUR_WEIGHT
+    }
+
+    // Counts the words in the dictionary that have the same letters
+    // except for one. The first letter isn’t changed because it is
+    // always visible in the game.
+    fn count_neighbours(&self, letters: &[char]) -> usize {
+        let mut pattern = String::new();
+
+        (1..letters.len()).map(|pos| {
+            pattern.clear();
+            pattern.extend(letters.iter().enumerate().map(|(index, &letter)| {
+                if index == pos {
+                    '?'
+                } else {
+                    letter
+                }
+            }));
+
+            self.dictionary.words_matching(&pattern)
+                .filter(|found| !found.word.chars().eq(letters.iter().copied()))
+                .count()
+        }).sum()
+    }
+}
+
+/// Splits words into bands of difficulty given their scores. The
+/// returned vec has the band of each score in the same order.
+pub fn bands(scores: &[f32]) -> Vec<Difficulty> {
+    let mut order = (0..scores.len()).collect::<Vec<_>>();
+
+    order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+
+    let mut bands = vec![Difficulty::Easy; scores.len()];
+
+    for (rank, index) in order.into_iter().enumerate() {
+        bands[index] = match rank * 3 / scores.len() {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        };
+    }
+
+    bands
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary;
+
+    #[test]
+    fn rate() {
+        let dictionary = Dictionary::new(Box::new(
+            dictionary::test::DICTIONARY_BYTES
+        ));
+        let language = Language::default();
+        let rater = Rater::new(&dictionary, &language);
+
+        // “p” is the most common letter in the dictionary
+        assert!(rater.rate("p") < rater.rate("b"));
+        // Repeated letters make it harder
+        assert!(rater.rate("pa") < rater.rate("pp"));
+        // Hatted letters make it harder
+        assert!(rater.rate("capelo") < rater.rate("ĉapelo"));
+        // The first letter is never changed so “a” has no neighbours
+        assert_eq!(rater.count_neighbours(&['a']), 0);
+        assert_eq!(rater.count_neighbours(&['a', 'p', 'q']), 1);
+        assert_eq!(rater.count_neighbours(&['a', 'p', 'p']), 0);
+
+        assert_eq!(rater.rate(""), 0.0);
+    }
+
+    #[test]
+    fn bands() {
+        assert_eq!(
+            super::bands(&[5.0, 1.0, 3.0, 2.0, 6.0, 4.0]),
+            [
+                Difficulty::Hard,
+                Difficulty::Easy,
+                Difficulty::Medium,
+                Difficulty::Easy,
+                Difficulty::Hard,
+                Difficulty::Medium,
+            ],
+        );
+
+        assert_eq!(super::bands(&[1.0]), [Difficulty::Easy]);
+        assert!(super::bands(&[]).is_empty());
+
+        assert_eq!("hard".parse(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+}
@@ -36,6 +36,9 @@ const FLASH_TIME: i64 = 1000;
 // Number of flashes per second
 const FLASHES_PER_SECOND: i64 = 4;
 
+// Time in milliseconds to flash the grid when a black ball is drawn
+const BLACK_BALL_TIME: i64 = 1500;
+
 // Total time for the bingo animation
 const BINGO_TIME: i64 = 3000;
 // Total time to reveal the BINGO letters
@@ -43,6 +46,7 @@ const BINGO_LETTER_TIME: i64 = BINGO_TIME / 2;
 
 const COVERED_COLOR: [u8; 3] = [0xe7, 0x00, 0x2a];
 const UNCOVERED_COLOR: [u8; 3] = [0x00, 0x77, 0xc7];
+const BLACK_BALL_COLOR: [u8; 3] = [0x20, 0x20, 0x20];
 
 struct Flash {
     start_time: timer::Timer,
@@ -57,11 +61,16 @@ struct FlashResult {
 struct AnimationTimes {
     flash: Option<FlashResult>,
     bingo_time: Option<i64>,
+    // Whether the uncovered spaces should currently be darkened
+    // because of a black ball
+    black_ball_dark: Option<bool>,
 }
 
 impl AnimationTimes {
     fn is_animating(&self) -> bool {
-        self.flash.is_some() || self.bingo_time.is_some()
+        self.flash.is_some()
+            || self.bingo_time.is_some()
+            || self.black_ball_dark.is_some()
     }
 }
 
@@ -82,6 +91,7 @@ pub struct BingoPainter {
     most_quads: u32,
     flash: Option<Flash>,
     bingo_start_time: Option<timer::Timer>,
+    black_ball_start_time: Option<timer::Timer>,
 }
 
 impl BingoPainter {
@@ -121,6 +131,7 @@ impl BingoPainter {
             most_quads: 0,
             flash: None,
             bingo_start_time: None,
+            black_ball_start_time: None,
         })
     }
 
@@ -152,9 +163,21 @@ impl BingoPainter {
             }
         });
 
+        let black_ball_dark = self.black_ball_start_time.and_then(|start_time| {
+            let millis = start_time.elapsed();
+
+            if millis < BLACK_BALL_TIME {
+                Some((millis * FLASHES_PER_SECOND / 1000) & 1 == 0)
+            } else {
+                self.black_ball_start_time = None;
+                None
+            }
+        });
+
         AnimationTimes {
             flash,
             bingo_time,
+            black_ball_dark,
         }
     }
 
@@ -238,6 +261,15 @@ impl BingoPainter {
                     false
                 }
             },
+            logic::Event::BlackBall(team) => {
+                if *team == self.team {
+                    self.black_ball_start_time = Some(timer::Timer::new());
+                    self.vertices_dirty = true;
+                    true
+                } else {
+                    false
+                }
+            },
             logic::Event::BingoReset(team) => {
                 if *team == self.team {
                     self.vertices_dirty = true;
@@ -250,7 +282,7 @@ impl BingoPainter {
             logic::Event::WordChanged => false,
             logic::Event::GridChanged => false,
             logic::Event::GuessEntered => false,
-            logic::Event::WrongGuessEntered => false,
+            logic::Event::WrongGuessEntered(_) => false,
             logic::Event::GuessRejected => false,
             logic::Event::Solved => false,
             logic::Event::ScoreChanged(_) => false,
@@ -258,6 +290,10 @@ impl BingoPainter {
             logic::Event::CurrentPageChanged(_) => false,
             logic::Event::SuperDivenoToggled => false,
             logic::Event::SuperDivenoPauseToggled => false,
+            logic::Event::GuessTimerChanged => false,
+            logic::Event::GuessTimeUp => false,
+            logic::Event::StealStarted => false,
+            logic::Event::SuperDivenoTimeUp => false,
         }
     }
 
@@ -387,6 +423,8 @@ impl BingoPainter {
 
         if covered {
             COVERED_COLOR
+        } else if animation_times.black_ball_dark == Some(true) {
+            BLACK_BALL_COLOR
         } else {
             UNCOVERED_COLOR
         }
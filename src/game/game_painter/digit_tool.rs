@@ -134,12 +134,14 @@ impl<'a> DigitTool<'a> {
         }
     }
 
-    fn add_frame(&mut self, x: f32, with_colon: bool) {
+    fn add_frame(&mut self, x: f32, y: f32, with_colon: bool) {
         let y_scale = self.width as f32 / self.height as f32;
 
         let (left, right) = DigitTool::left_right(x, with_colon);
-        let top = (DIGIT_HEIGHT / 2.0 + INNER_GAP_SIZE + FRAME_WIDTH) * y_scale;
-        let bottom = -top;
+        let half_height =
+            (DIGIT_HEIGHT / 2.0 + INNER_GAP_SIZE + FRAME_WIDTH) * y_scale;
+        let top = y + half_height;
+        let bottom = y - half_height;
 
         // Left side
         self.add_quad(
@@ -258,15 +260,16 @@ impl<'a> DigitTool<'a> {
         );
     }
 
-    fn add_inner_gap(&mut self, x: f32, with_colon: bool) {
+    fn add_inner_gap(&mut self, x: f32, y: f32, with_colon: bool) {
         let y_scale = self.width as f32 / self.height as f32;
 
         let (left, right) = DigitTool::left_right(x, with_colon);
 
         let left = left + FRAME_WIDTH;
         let right = right - FRAME_WIDTH;
-        let top = (DIGIT_HEIGHT / 2.0 + INNER_GAP_SIZE) * y_scale;
-        let bottom = -top;
+        let half_height = (DIGIT_HEIGHT / 2.0 + INNER_GAP_SIZE) * y_scale;
+        let top = y + half_height;
+        let bottom = y - half_height;
 
         // Left side
         self.add_gap_quad(
@@ -333,15 +336,21 @@ impl<'a> DigitTool<'a> {
         );
     }
 
-    fn add_digits(&mut self, x: f32, with_colon: bool, mut value: u32) {
+    fn add_digits(
+        &mut self,
+        x: f32,
+        y: f32,
+        with_colon: bool,
+        mut value: u32,
+    ) {
         let y_scale = self.width as f32 / self.height as f32;
 
         let (edge_left, edge_right) = DigitTool::left_right(x, with_colon);
         let edge_left = edge_left + FRAME_WIDTH + INNER_GAP_SIZE;
         let edge_right = edge_right - FRAME_WIDTH - INNER_GAP_SIZE;
         let mut right = edge_right;
-        let top = DIGIT_HEIGHT / 2.0 * y_scale;
-        let bottom = -top;
+        let top = y + DIGIT_HEIGHT / 2.0 * y_scale;
+        let bottom = y - DIGIT_HEIGHT / 2.0 * y_scale;
 
         for digit_num in 0..N_DIGITS {
             if with_colon && digit_num == N_DIGITS - 1 {
@@ -385,10 +394,18 @@ impl<'a> DigitTool<'a> {
         }
     }
 
-    pub fn add_display(&mut self, x: f32, value: u32, with_colon: bool) {
-        self.add_frame(x, with_colon);
-        self.add_inner_gap(x, with_colon);
-        self.add_digits(x, with_colon, value);
+    // Adds a display whose left edge is at x and which is vertically
+    // centred on y
+    pub fn add_display(
+        &mut self,
+        x: f32,
+        y: f32,
+        value: u32,
+        with_colon: bool,
+    ) {
+        self.add_frame(x, y, with_colon);
+        self.add_inner_gap(x, y, with_colon);
+        self.add_digits(x, y, with_colon, value);
     }
 }
 
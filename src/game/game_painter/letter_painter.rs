@@ -17,8 +17,7 @@
 use std::rc::Rc;
 use super::super::paint_data::PaintData;
 use super::super::buffer::Buffer;
-use super::super::{logic, timer, letter_texture, timeout};
-use letter_texture::LETTERS;
+use super::super::{logic, timer, timeout};
 use super::super::array_object::ArrayObject;
 use glow::HasContext;
 use nalgebra::{Vector3, Perspective3, Matrix4};
@@ -32,13 +31,22 @@ use super::super::timing::MILLIS_PER_LETTER;
 // Time for a letter to turn
 const TURN_TIME: i64 = 500;
 
-// How long to shake the guess for when a wrong guess is entered
+// How long to shake the guess for when a guess with the wrong length
+// is entered
 const SHAKE_TIME: i64 = 500;
 // The frequency of the shaking in shakes per second
 const SHAKE_FREQUENCY: i64 = 20;
 // Distance to move the tile while shaking where 1 is the size of a tile
 const SHAKE_DISTANCE: f32 = 0.1;
 
+// How long to flash the guess for when a word that isn’t in the
+// dictionary is entered
+const FLASH_TIME: i64 = 600;
+// The time for one flash to turn on and off again
+const FLASH_PERIOD: i64 = 200;
+// Color of the tiles while they are flashing
+const FLASH_COLOR: [u8; 3] = [0x90, 0x00, 0x00];
+
 // The time for one tile to raise and lower itself again
 const WAVE_LIFT_TIME: i64 = 300;
 // The time between starting each tile
@@ -49,12 +57,17 @@ const WAVE_LIFT_DISTANCE: f32 = 0.2;
 // Time to wait after the last guess reveal animation before revealing
 // the answer
 const ANSWER_DELAY: i64 = 1000;
+// The smallest number of tiles to fit along the height or half of
+// the width of the screen
+const MIN_TILES: usize = 10;
 
 const EMPTY_COLOR: [u8; 3] = [0; 3];
+// Color of the tiles hiding the letters of a word typed by the host
+const MASKED_COLOR: [u8; 3] = [0x68, 0x68, 0x68];
 
 struct AnimationTimes {
     reveal_time: Option<i64>,
-    shake_time: Option<i64>,
+    wrong_guess_time: Option<(logic::WrongGuessReason, i64)>,
     wave_time: Option<i64>,
     answer_time: Option<i64>,
 }
@@ -62,7 +75,7 @@ struct AnimationTimes {
 impl AnimationTimes {
     fn is_animating(&self) -> bool {
         self.reveal_time.is_some()
-            || self.shake_time.is_some()
+            || self.wrong_guess_time.is_some()
             || self.wave_time.is_some()
             || self.answer_time.is_some()
     }
@@ -83,7 +96,7 @@ pub struct LetterPainter {
     // Used to keep track of whether we need to create a new quad buffer
     most_quads: u32,
     reveal_start_time: Option<timer::Timer>,
-    shake_start_time: Option<timer::Timer>,
+    wrong_guess_start_time: Option<(logic::WrongGuessReason, timer::Timer)>,
     wave_start_time: Option<timer::Timer>,
     answer_start_time: Option<timer::Timer>,
 }
@@ -118,7 +131,7 @@ impl LetterPainter {
             vertices: Vec::new(),
             most_quads: 0,
             reveal_start_time: None,
-            shake_start_time: None,
+            wrong_guess_start_time: None,
             wave_start_time: None,
             answer_start_time: None,
         })
@@ -144,25 +157,35 @@ impl LetterPainter {
             }
         });
 
-        let shake_time = self.shake_start_time.and_then(|start_time| {
-            let millis = start_time.elapsed();
+        let total_wave_time =
+            (logic.word_length() as i64 - 1)
+            * WAVE_LIFT_DELAY
+            + WAVE_LIFT_TIME;
+
+        let wrong_guess_time = self.wrong_guess_start_time.and_then(
+            |(reason, start_time)| {
+                let millis = start_time.elapsed();
+
+                let total_time = match reason {
+                    logic::WrongGuessReason::WrongLength => SHAKE_TIME,
+                    logic::WrongGuessReason::NotInDictionary => FLASH_TIME,
+                    logic::WrongGuessReason::AlreadyTried(_) => {
+                        total_wave_time
+                    },
+                };
 
-            if millis < SHAKE_TIME {
-                Some(millis)
-            } else {
-                self.shake_start_time = None;
-                None
+                if millis < total_time {
+                    Some((reason, millis))
+                } else {
+                    self.wrong_guess_start_time = None;
+                    None
+                }
             }
-        });
+        );
 
         let wave_time = self.wave_start_time.and_then(|start_time| {
             let millis = start_time.elapsed();
 
-            let total_wave_time =
-                (logic.word_length() as i64 - 1)
-                * WAVE_LIFT_DELAY
-                + WAVE_LIFT_TIME;
-
             if millis < total_reveal_time + total_wave_time {
                 Some(millis - total_reveal_time)
             } else {
@@ -184,7 +207,7 @@ impl LetterPainter {
 
         AnimationTimes {
             reveal_time,
-            shake_time,
+            wrong_guess_time,
             wave_time,
             answer_time,
         }
@@ -266,7 +289,7 @@ impl LetterPainter {
             logic::Event::GuessEntered => {
                 self.reveal_start_time = Some(timer::Timer::new());
 
-                if logic.n_guesses() >= logic::N_GUESSES && !logic.is_solved() {
+                if logic.is_finished() && !logic.is_solved() {
                     self.answer_start_time = self.reveal_start_time;
                 }
 
@@ -274,13 +297,14 @@ impl LetterPainter {
 
                 true
             },
-            logic::Event::WrongGuessEntered => {
-                self.shake_start_time = Some(timer::Timer::new());
+            logic::Event::WrongGuessEntered(reason) => {
+                self.wrong_guess_start_time =
+                    Some((*reason, timer::Timer::new()));
                 self.vertices_dirty = true;
                 true
             },
             logic::Event::GuessRejected => {
-                if logic.n_guesses() >= logic::N_GUESSES && !logic.is_solved() {
+                if logic.is_finished() && !logic.is_solved() {
                     self.answer_start_time = Some(timer::Timer::new());
                 }
 
@@ -300,18 +324,33 @@ impl LetterPainter {
             logic::Event::BingoReset(_) => false,
             logic::Event::BingoChanged(..) => false,
             logic::Event::Bingo(..) => false,
+            logic::Event::BlackBall(_) => false,
             logic::Event::SuperDivenoToggled => false,
             logic::Event::SuperDivenoPauseToggled => false,
+            logic::Event::GuessTimerChanged => false,
+            logic::Event::GuessTimeUp => false,
+            logic::Event::StealStarted => {
+                self.vertices_dirty = true;
+                true
+            },
+            logic::Event::SuperDivenoTimeUp => false,
         }
     }
 
     fn update_transform(&mut self, logic: &logic::Logic) {
-        // We want to fit ten tiles along either the height or half of
-        // the width, whichever is smaller. We only use half of the
-        // width so that there will be a quarter of the space on
-        // either side in order to draw the scores.
+        // We want to fit enough tiles for the word and the guesses,
+        // plus a spare row, along either the height or half of the
+        // width, whichever is smaller. We only use half of the width
+        // so that there will be a quarter of the space on either side
+        // in order to draw the scores. There is always room for at
+        // least ten tiles so that the tiles don’t get too big for
+        // short games.
         let smallest_axis = (self.width / 2).clamp(1, self.height);
-        const TILE_SIZE: f32 = 2.0 / 10.0;
+        // Leave space for the extra row if the word can be stolen so
+        // that the grid doesn’t move when it is
+        let n_rows = logic.rules().n_guesses + logic.rules().steal as usize;
+        let n_tiles = logic.word_length().max(n_rows + 1).max(MIN_TILES);
+        let tile_size = 2.0 / n_tiles as f32;
         const FOV: f32 = PI / 4.0;
 
         let y_top = self.height as f32 / smallest_axis as f32;
@@ -324,21 +363,21 @@ impl LetterPainter {
         let perspective = Perspective3::new(
             self.width as f32 / self.height as f32,
             FOV,
-            zero_distance - TILE_SIZE * 2.0,
-            zero_distance + TILE_SIZE * 2.0,
+            zero_distance - tile_size * 2.0,
+            zero_distance + tile_size * 2.0,
         );
 
         self.mvp_matrix = perspective
             .as_matrix()
             .prepend_translation(&Vector3::new(0.0, 0.0, -zero_distance))
             .prepend_nonuniform_scaling(&Vector3::new(
-                TILE_SIZE,
-                -TILE_SIZE,
-                TILE_SIZE,
+                tile_size,
+                -tile_size,
+                tile_size,
             ))
             .prepend_translation(&Vector3::new(
                 -(logic.word_length() as f32) / 2.0,
-                -(logic::N_GUESSES as f32) / 2.0,
+                -(n_rows as f32) / 2.0,
                 0.0,
             ));
 
@@ -364,18 +403,24 @@ impl LetterPainter {
                     animation_times.reveal_time.unwrap()
                 );
             } else {
-                self.add_guess(
-                    guess,
-                    guess_num as u32,
-                    animation_times.wave_time
-                );
+                // Lift the guess if it matches a guess that the team
+                // tried to enter again
+                let wave_time = match animation_times.wrong_guess_time {
+                    Some((
+                        logic::WrongGuessReason::AlreadyTried(tried_num),
+                        wrong_guess_time,
+                    )) if tried_num == guess_num => Some(wrong_guess_time),
+                    _ => animation_times.wave_time,
+                };
+
+                self.add_guess(guess, guess_num as u32, wave_time);
             }
 
             guess_num += 1;
         }
 
-        if guess_num < logic::N_GUESSES {
-            if !logic.is_finished() {
+        if guess_num < logic.max_guesses() {
+            if !logic.is_finished() || logic.typed_word_length().is_some() {
                 let visible_letters = if animation_times.reveal_time.is_some() {
                     0
                 } else {
@@ -386,7 +431,7 @@ impl LetterPainter {
                     logic,
                     guess_num as u32,
                     visible_letters,
-                    animation_times.shake_time,
+                    animation_times.wrong_guess_time,
                 );
 
                 guess_num += 1;
@@ -398,7 +443,7 @@ impl LetterPainter {
                     x
                 );
 
-                for y in guess_num..logic::N_GUESSES {
+                for y in guess_num..logic.max_guesses() {
                     self.add_letter(
                         EMPTY_COLOR,
                         x as f32,
@@ -407,6 +452,15 @@ impl LetterPainter {
                     );
                 }
             }
+        } else if let Some(typed_word_length) = logic.typed_word_length() {
+            // Show the typed word in place of the answer
+            self.add_typed_word(
+                logic,
+                guess_num as u32,
+                typed_word_length,
+                0.0,
+                false,
+            );
         } else if !logic.is_solved() {
             self.add_answer(logic, animation_times.answer_time);
         }
@@ -473,20 +527,40 @@ impl LetterPainter {
         logic: &logic::Logic,
         y: u32,
         visible_letters: u32,
-        shake_time: Option<i64>,
+        wrong_guess_time: Option<(logic::WrongGuessReason, i64)>,
     ) {
         let mut added = 0;
 
-        let shake_offset = shake_time
-            .map(|t| {
+        let shake_offset = match wrong_guess_time {
+            Some((logic::WrongGuessReason::WrongLength, t)) => {
                 (((t * SHAKE_FREQUENCY / 1000) & 1) * 2 - 1) as f32
                     * SHAKE_DISTANCE
-            })
-            .unwrap_or(0.0);
+            },
+            _ => 0.0,
+        };
+
+        let flash = matches!(
+            wrong_guess_time,
+            Some((logic::WrongGuessReason::NotInDictionary, t))
+                if t % FLASH_PERIOD < FLASH_PERIOD / 2
+        );
+
+        if let Some(typed_word_length) = logic.typed_word_length() {
+            self.add_typed_word(
+                logic,
+                y,
+                typed_word_length,
+                shake_offset,
+                flash,
+            );
+            return;
+        }
+
+        let color = if flash { FLASH_COLOR } else { EMPTY_COLOR };
 
         for (pos, ch) in logic.in_progress_guess().chars().enumerate() {
             self.add_letter(
-                EMPTY_COLOR,
+                color,
                 pos as f32 + shake_offset,
                 y as f32,
                 ch
@@ -503,7 +577,7 @@ impl LetterPainter {
                 };
 
                 self.add_letter(
-                    EMPTY_COLOR,
+                    color,
                     index as f32 + shake_offset,
                     y as f32,
                     ch
@@ -512,7 +586,7 @@ impl LetterPainter {
         } else {
             for x in added..logic.word_length() {
                 self.add_letter(
-                    EMPTY_COLOR,
+                    color,
                     x as f32 + shake_offset,
                     y as f32,
                     '.'
@@ -521,6 +595,29 @@ impl LetterPainter {
         }
     }
 
+    // Adds a row of blank tiles for each letter of the word that the
+    // host is typing so that the players can’t see it
+    fn add_typed_word(
+        &mut self,
+        logic: &logic::Logic,
+        y: u32,
+        typed_word_length: usize,
+        shake_offset: f32,
+        flash: bool,
+    ) {
+        for x in 0..typed_word_length.max(logic.word_length()) {
+            let color = if flash {
+                FLASH_COLOR
+            } else if x < typed_word_length {
+                MASKED_COLOR
+            } else {
+                EMPTY_COLOR
+            };
+
+            self.add_letter(color, x as f32 + shake_offset, y as f32, ' ');
+        }
+    }
+
     fn add_answer(&mut self, logic: &logic::Logic, answer_time: Option<i64>) {
         match answer_time {
             Some(answer_time) => {
@@ -537,7 +634,7 @@ impl LetterPainter {
                     self.add_rotated_letter(
                         EMPTY_COLOR,
                         x as f32,
-                        logic::N_GUESSES as f32,
+                        logic.max_guesses() as f32,
                         rotation_progress,
                         letter
                     );
@@ -548,7 +645,7 @@ impl LetterPainter {
                     self.add_letter(
                         EMPTY_COLOR,
                         x as f32,
-                        logic::N_GUESSES as f32,
+                        logic.max_guesses() as f32,
                         letter
                     );
                 }
@@ -601,15 +698,11 @@ impl LetterPainter {
         rotation_progress: f32,
         letter: char
     ) {
-        let Ok(letter_index) = LETTERS.binary_search_by(|probe| {
-            probe.ch.cmp(&letter)
-        })
+        let Some(letter) = self.paint_data.language.tile(letter)
         else {
             return;
         };
 
-        let letter = &LETTERS[letter_index];
-
         self.vertices.push(Vertex {
             x,
             y,
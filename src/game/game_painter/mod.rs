@@ -73,7 +73,7 @@ pub struct GamePainter {
     all_score_painter: ScorePainter,
     letter_painter: LetterPainter,
     super_painter: SuperPainter,
-    team_painters: [TeamPainters; logic::N_TEAMS],
+    team_painters: Vec<TeamPainters>,
     width: u32,
     height: u32,
     viewport_dirty: bool,
@@ -81,51 +81,40 @@ pub struct GamePainter {
 }
 
 impl GamePainter {
-    pub fn new(paint_data: Rc<PaintData>) -> Result<GamePainter, String> {
+    pub fn new(
+        paint_data: Rc<PaintData>,
+        n_teams: usize,
+    ) -> Result<GamePainter, String> {
         let gl = &paint_data.gl;
 
         unsafe {
             gl.enable(glow::CULL_FACE);
         }
 
+        let mut team_painters = Vec::with_capacity(n_teams);
+
+        for team in (0..n_teams).map(Team) {
+            team_painters.push(TeamPainters {
+                tombola: TombolaPainter::new(Rc::clone(&paint_data), team)?,
+                score: ScorePainter::new(
+                    Rc::clone(&paint_data),
+                    n_teams,
+                    score_painter::TeamChoice::OneTeam(team),
+                )?,
+                bingo: BingoPainter::new(Rc::clone(&paint_data), team)?,
+            });
+        }
+
         Ok(GamePainter {
             paint_data: Rc::clone(&paint_data),
             all_score_painter: ScorePainter::new(
                 Rc::clone(&paint_data),
+                n_teams,
                 score_painter::TeamChoice::AllTeams,
             )?,
             letter_painter: LetterPainter::new(Rc::clone(&paint_data))?,
             super_painter: SuperPainter::new(Rc::clone(&paint_data))?,
-            team_painters: [
-                TeamPainters {
-                    tombola: TombolaPainter::new(
-                        Rc::clone(&paint_data),
-                        Team::Left,
-                    )?,
-                    score: ScorePainter::new(
-                        Rc::clone(&paint_data),
-                        score_painter::TeamChoice::OneTeam(Team::Left),
-                    )?,
-                    bingo: BingoPainter::new(
-                        Rc::clone(&paint_data),
-                        Team::Left,
-                    )?,
-                },
-                TeamPainters {
-                    tombola: TombolaPainter::new(
-                        Rc::clone(&paint_data),
-                        Team::Right,
-                    )?,
-                    score: ScorePainter::new(
-                        Rc::clone(&paint_data),
-                        score_painter::TeamChoice::OneTeam(Team::Right),
-                    )?,
-                    bingo: BingoPainter::new(
-                        Rc::clone(&paint_data),
-                        Team::Right,
-                    )?,
-                },
-            ],
+            team_painters,
             width: 1,
             height: 1,
             viewport_dirty: true,
@@ -136,7 +125,7 @@ impl GamePainter {
     fn paint_page(&mut self, logic: &mut Logic, page: Page) -> Timeout {
         match page {
             Page::Bingo(team) => {
-                let painters = &mut self.team_painters[team as usize];
+                let painters = &mut self.team_painters[team.0];
                 painters.tombola.paint(logic)
                     .min(painters.score.paint(logic))
                     .min(painters.bingo.paint(logic))
@@ -257,8 +246,8 @@ impl GamePainter {
             redraw_needed = true;
         }
 
-        for team in [Team::Left, Team::Right] {
-            let painters = &mut self.team_painters[team as usize];
+        for (team_num, painters) in self.team_painters.iter_mut().enumerate() {
+            let team = Team(team_num);
 
             let team_redraw_needed =
                 painters.tombola.handle_logic_event(logic, event)
@@ -280,6 +269,7 @@ impl GamePainter {
         logic: &Logic,
     ) -> AnimationPosition {
         let current_page = logic.current_page();
+        let n_teams = logic.n_teams();
 
         match self.page_animation {
             Some(PageAnimation { start_time, start_page }) => {
@@ -292,7 +282,9 @@ impl GamePainter {
                     // Ease-in cubic
                     let delta = delta * delta * delta;
 
-                    if current_page.position() < start_page.position() {
+                    if current_page.position(n_teams)
+                        < start_page.position(n_teams)
+                    {
                         AnimationPosition::TwoPages {
                             left: current_page,
                             right: start_page,
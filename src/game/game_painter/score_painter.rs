@@ -26,8 +26,6 @@ use glow::HasContext;
 
 // Number of quads needed to draw the bar to show the current team
 const N_BAR_QUADS: usize = 1;
-// Total number of quads to draw the two score boards
-const TOTAL_N_QUADS: usize = digit_tool::TOTAL_N_QUADS * 2 + N_BAR_QUADS;
 
 // Milliseconds per unit change when animating the score
 const SCORE_CHANGE_TIME: i64 = 30;
@@ -55,6 +53,9 @@ struct AnimatedScore {
 
 pub struct ScorePainter {
     team_choice: TeamChoice,
+    n_teams: usize,
+    // Total number of quads needed to draw the score boards
+    n_quads: usize,
     buffer: Rc<Buffer>,
     array_object: ArrayObject,
     paint_data: Rc<PaintData>,
@@ -63,32 +64,47 @@ pub struct ScorePainter {
     vertices_dirty: bool,
     // Temporary buffer used for building the vertex buffer
     vertices: Vec<Vertex>,
-    animated_scores: [Option<AnimatedScore>; logic::N_TEAMS],
-    last_scores: [u32; logic::N_TEAMS],
+    animated_scores: Vec<Option<AnimatedScore>>,
+    last_scores: Vec<u32>,
+    // Number of seconds shown on the guess countdown if it is visible
+    last_guess_seconds: Option<u32>,
 }
 
 impl ScorePainter {
     pub fn new(
         paint_data: Rc<PaintData>,
+        n_teams: usize,
         team_choice: TeamChoice,
     ) -> Result<ScorePainter, String> {
+        // The page with all of the teams also has a display for the
+        // guess countdown
+        let n_displays = match team_choice {
+            TeamChoice::OneTeam(_) => 1,
+            TeamChoice::AllTeams => n_teams + 1,
+        };
+        let n_quads = digit_tool::TOTAL_N_QUADS * n_displays + N_BAR_QUADS;
+
         let buffer = create_score_buffer(&paint_data)?;
         let array_object = create_array_object(
             &paint_data,
             Rc::clone(&buffer),
+            n_quads,
         )?;
 
         Ok(ScorePainter {
             team_choice,
+            n_teams,
+            n_quads,
             buffer,
             array_object,
             paint_data,
             width: 1,
             height: 1,
             vertices_dirty: true,
-            vertices: Vec::with_capacity(TOTAL_N_QUADS * 4),
-            animated_scores: Default::default(),
-            last_scores: Default::default(),
+            vertices: Vec::with_capacity(n_quads * 4),
+            animated_scores: (0..n_teams).map(|_| None).collect(),
+            last_scores: vec![0; n_teams],
+            last_guess_seconds: None,
         })
     }
 
@@ -104,6 +120,20 @@ impl ScorePainter {
             return Timeout::Forever;
         }
 
+        let guess_remaining_time = match self.team_choice {
+            TeamChoice::OneTeam(_) => None,
+            TeamChoice::AllTeams => logic.guess_remaining_time(),
+        };
+
+        let guess_seconds = guess_remaining_time.map(|remaining_time| {
+            ((remaining_time + 999) / 1000) as u32
+        });
+
+        if guess_seconds != self.last_guess_seconds {
+            self.last_guess_seconds = guess_seconds;
+            self.vertices_dirty = true;
+        }
+
         if self.vertices_dirty {
             self.update_vertices(logic);
             self.vertices_dirty = false;
@@ -138,6 +168,8 @@ impl ScorePainter {
         if self.animated_scores.iter().any(|s| s.is_some()) {
             self.vertices_dirty = true;
             timeout::IMMEDIATELY
+        } else if let Some(remaining_time) = guess_remaining_time {
+            Timeout::Milliseconds(remaining_time % 1000 + 1)
         } else {
             Timeout::Forever
         }
@@ -158,13 +190,27 @@ impl ScorePainter {
             logic::Event::WordChanged => false,
             logic::Event::GridChanged => false,
             logic::Event::GuessEntered => false,
-            logic::Event::WrongGuessEntered => false,
+            logic::Event::WrongGuessEntered(_) => false,
             logic::Event::GuessRejected => false,
             logic::Event::CurrentPageChanged(_) => false,
             logic::Event::TombolaStartedSpinning(_) => false,
             logic::Event::BingoReset(_) => false,
             logic::Event::BingoChanged(..) => false,
+            logic::Event::BlackBall(_) => false,
             logic::Event::SuperDivenoPauseToggled => false,
+            logic::Event::SuperDivenoTimeUp => false,
+            logic::Event::GuessTimeUp => false,
+            logic::Event::StealStarted => false,
+            logic::Event::GuessTimerChanged => {
+                if logic.super_diveno().is_none()
+                    && matches!(self.team_choice, TeamChoice::AllTeams)
+                {
+                    self.vertices_dirty = true;
+                    true
+                } else {
+                    false
+                }
+            },
             logic::Event::Bingo(team, _) => {
                 if logic.super_diveno().is_none() &&
                     self.team_is_visible(*team)
@@ -227,12 +273,12 @@ impl ScorePainter {
     }
 
     fn animate_score_change(&mut self, team: logic::Team, delay: i64) {
-        let slot = &mut self.animated_scores[team as usize];
+        let slot = &mut self.animated_scores[team.0];
 
         match slot.as_mut() {
             None => {
                 *slot = Some(AnimatedScore {
-                    start_score: self.last_scores[team as usize],
+                    start_score: self.last_scores[team.0],
                     start_time: timer::Timer::new(),
                     delay,
                 });
@@ -253,7 +299,7 @@ impl ScorePainter {
     ) -> u32 {
         let target_score = logic.team_score(team);
 
-        let paint_score = match self.animated_scores[team as usize] {
+        let paint_score = match self.animated_scores[team.0] {
             Some(ref animated_score) => {
                 let score_diff = animated_score.start_score
                     .abs_diff(target_score) as i64;
@@ -263,7 +309,7 @@ impl ScorePainter {
                     .max(0);
 
                 if elapsed >= total_time {
-                    self.animated_scores[team as usize] = None;
+                    self.animated_scores[team.0] = None;
                     target_score
                 } else {
                     (animated_score.start_score as i64
@@ -278,7 +324,7 @@ impl ScorePainter {
             }
         };
 
-        self.last_scores[team as usize] = paint_score;
+        self.last_scores[team.0] = paint_score;
 
         paint_score
     }
@@ -300,15 +346,44 @@ impl ScorePainter {
         self.vertices.push(Vertex { x: x2, y: y2, s: s1, t: t2, });
     }
 
-    fn add_current_team(&mut self, logic: &logic::Logic) {
-        let x = match logic.current_team() {
-            logic::Team::Left => -1.0,
-            logic::Team::Right => 1.0 - digit_tool::DISPLAY_WIDTH,
+    // Returns the left edge and the vertical centre of the scoreboard
+    // for the team. The teams are split between the two sides of the
+    // screen and stacked vertically.
+    fn display_position(&self, team: logic::Team) -> (f32, f32) {
+        let n_left = logic::n_left_teams(self.n_teams);
+        let is_left = team.0 < n_left;
+
+        let x = if is_left {
+            -1.0
+        } else {
+            1.0 - digit_tool::DISPLAY_WIDTH
         };
 
+        let (index, n_on_side) = match self.team_choice {
+            TeamChoice::OneTeam(_) => (0, 1),
+            TeamChoice::AllTeams => {
+                if is_left {
+                    (team.0, n_left)
+                } else {
+                    (team.0 - n_left, self.n_teams - n_left)
+                }
+            },
+        };
+
+        let y_scale = self.width as f32 / self.height as f32;
+        let spacing = (digit_tool::TOTAL_HEIGHT + BAR_HEIGHT) * y_scale;
+
+        let y = ((n_on_side - 1) as f32 / 2.0 - index as f32) * spacing;
+
+        (x, y)
+    }
+
+    fn add_current_team(&mut self, logic: &logic::Logic) {
+        let (x, y) = self.display_position(logic.current_team());
+
         let y_scale = self.width as f32 / self.height as f32;
 
-        let y = -digit_tool::TOTAL_HEIGHT / 2.0 * y_scale;
+        let y = y - digit_tool::TOTAL_HEIGHT / 2.0 * y_scale;
 
         self.add_quad_rotated_tex(
             x + digit_tool::OUTER_GAP_SIZE,
@@ -322,43 +397,54 @@ impl ScorePainter {
         );
     }
 
-    fn add_scoreboard(&mut self, x: f32, score: u32) {
+    // Adds the guess countdown underneath the bar for the current team
+    fn add_guess_countdown(&mut self, logic: &logic::Logic, seconds: u32) {
+        let (x, y) = self.display_position(logic.current_team());
+
+        let y_scale = self.width as f32 / self.height as f32;
+
+        let y = y - (digit_tool::TOTAL_HEIGHT + BAR_HEIGHT) * y_scale;
+
+        let mut digit_tool = digit_tool::DigitTool::new(
+            &mut self.vertices,
+            self.width,
+            self.height,
+        );
+
+        digit_tool.add_display(x, y, seconds, false);
+    }
+
+    fn add_scoreboard(&mut self, team: logic::Team, score: u32) {
+        let (x, y) = self.display_position(team);
+
         let mut digit_tool = digit_tool::DigitTool::new(
             &mut self.vertices,
             self.width,
             self.height,
         );
 
-        digit_tool.add_display(x, score, false);
+        digit_tool.add_display(x, y, score, false);
     }
 
     fn fill_vertices_array(&mut self, logic: &logic::Logic) {
         self.vertices.clear();
 
-        if self.team_is_visible(logic::Team::Left) {
-            let left_score = self.update_animated_score(
-                logic,
-                logic::Team::Left
-            );
-            self.add_scoreboard(-1.0, left_score);
-        }
-
-        if self.team_is_visible(logic::Team::Right) {
-            let right_score = self.update_animated_score(
-                logic,
-                logic::Team::Right
-            );
-            self.add_scoreboard(
-                1.0 - digit_tool::DISPLAY_WIDTH,
-                right_score,
-            );
+        for team in (0..self.n_teams).map(logic::Team) {
+            if self.team_is_visible(team) {
+                let score = self.update_animated_score(logic, team);
+                self.add_scoreboard(team, score);
+            }
         }
 
         if self.team_is_visible(logic.current_team()) {
             self.add_current_team(logic);
         }
 
-        assert!(self.vertices.len() <= TOTAL_N_QUADS * 4);
+        if let Some(seconds) = self.last_guess_seconds {
+            self.add_guess_countdown(logic, seconds);
+        }
+
+        assert!(self.vertices.len() <= self.n_quads * 4);
     }
 
     fn update_vertices(&mut self, logic: &logic::Logic) {
@@ -386,12 +472,13 @@ impl ScorePainter {
 fn create_array_object(
     paint_data: &Rc<PaintData>,
     buffer: Rc<Buffer>,
+    n_quads: usize,
 ) -> Result<ArrayObject, String> {
     let mut array_object = digit_tool::create_array_object(paint_data, buffer)?;
 
     paint_data.quad_tool.set_element_buffer(
         &mut array_object,
-        TOTAL_N_QUADS as u32,
+        n_quads as u32,
     )?;
 
     Ok(array_object)
@@ -27,6 +27,11 @@ use glow::HasContext;
 const TOTAL_N_QUADS: usize = digit_tool::TOTAL_N_QUADS * 2
     + digit_tool::N_COLON_QUADS;
 
+// How long to flash the displays for when the time runs out
+const TIME_UP_FLASH_TIME: i64 = 3000;
+// The time that the displays are visible or hidden while flashing
+const TIME_UP_FLASH_PERIOD: i64 = 250;
+
 pub struct SuperPainter {
     buffer: Rc<Buffer>,
     array_object: ArrayObject,
@@ -36,6 +41,7 @@ pub struct SuperPainter {
     last_remaining_seconds: u32,
     vertices_dirty: bool,
     score_delay: Option<timer::Timer>,
+    time_up_start_time: Option<timer::Timer>,
     // Temporary buffer used for building the vertex buffer
     vertices: Vec<digit_tool::Vertex>,
 }
@@ -55,6 +61,7 @@ impl SuperPainter {
             width: 1,
             height: 1,
             score_delay: None,
+            time_up_start_time: None,
             last_remaining_seconds: u32::MAX,
             vertices_dirty: true,
             vertices: Vec::with_capacity(TOTAL_N_QUADS * 4),
@@ -69,6 +76,22 @@ impl SuperPainter {
 
         self.update_vertices(logic, &super_diveno);
 
+        let flash_time = self.time_up_flash_time();
+
+        let flash_timeout = match flash_time {
+            Some(millis) => Timeout::Milliseconds(
+                TIME_UP_FLASH_PERIOD - millis % TIME_UP_FLASH_PERIOD
+            ),
+            None => Timeout::Forever,
+        };
+
+        // Leave the displays hidden for every other period
+        if flash_time.is_some_and(|millis| {
+            (millis / TIME_UP_FLASH_PERIOD) & 1 == 1
+        }) {
+            return flash_timeout;
+        }
+
         self.array_object.bind();
 
         let gl = &self.paint_data.gl;
@@ -94,18 +117,35 @@ impl SuperPainter {
             gl.disable(glow::BLEND);
         }
 
-        let timer_timeout = if super_diveno.is_paused() {
+        let timer_timeout = if super_diveno.is_paused()
+            || super_diveno.is_time_up()
+        {
             Timeout::Forever
         } else {
             Timeout::Milliseconds(super_diveno.remaining_time() % 1000 + 1)
         };
 
+        let timer_timeout = timer_timeout.min(flash_timeout);
+
         match self.score_delay_time(logic) {
             Some(delay) => Timeout::Milliseconds(delay).min(timer_timeout),
             None => timer_timeout,
         }
     }
 
+    // Returns the time since the displays started flashing, or None
+    // if they have stopped.
+    fn time_up_flash_time(&mut self) -> Option<i64> {
+        let millis = self.time_up_start_time?.elapsed();
+
+        if millis < TIME_UP_FLASH_TIME {
+            Some(millis)
+        } else {
+            self.time_up_start_time = None;
+            None
+        }
+    }
+
     pub fn update_fb_size(&mut self, width: u32, height: u32) {
         self.width = width;
         self.height = height;
@@ -121,15 +161,25 @@ impl SuperPainter {
             logic::Event::WordChanged => false,
             logic::Event::GridChanged => false,
             logic::Event::GuessEntered => false,
-            logic::Event::WrongGuessEntered => false,
+            logic::Event::WrongGuessEntered(_) => false,
             logic::Event::GuessRejected => false,
             logic::Event::CurrentPageChanged(_) => false,
             logic::Event::TombolaStartedSpinning(_) => false,
             logic::Event::BingoReset(_) => false,
             logic::Event::BingoChanged(..) => false,
             logic::Event::Bingo(..) => false,
-            logic::Event::ScoreChanged(..) => false,
+            logic::Event::BlackBall(_) => false,
             logic::Event::CurrentTeamChanged => false,
+            // This is only sent in super diveno mode when the number
+            // of guessed words is restored with the undo keys
+            logic::Event::ScoreChanged(..) => {
+                if logic.super_diveno().is_some() {
+                    self.vertices_dirty = true;
+                    true
+                } else {
+                    false
+                }
+            },
             logic::Event::Solved => {
                 if logic.super_diveno().is_some() {
                     self.score_delay = Some(timer::Timer::new());
@@ -141,10 +191,21 @@ impl SuperPainter {
             logic::Event::SuperDivenoToggled => {
                 if logic.super_diveno().is_some() {
                     self.vertices_dirty = true;
+                } else {
+                    self.time_up_start_time = None;
                 }
                 true
             },
             logic::Event::SuperDivenoPauseToggled => true,
+            logic::Event::GuessTimerChanged => false,
+            logic::Event::GuessTimeUp => false,
+            logic::Event::StealStarted => false,
+            logic::Event::SuperDivenoTimeUp => {
+                self.time_up_start_time = Some(timer::Timer::new());
+                // The score display changes to show the points
+                self.vertices_dirty = true;
+                true
+            },
         }
     }
 
@@ -164,8 +225,13 @@ impl SuperPainter {
         let minutes_seconds = remaining_seconds % 60
             + remaining_seconds / 60 * 100;
 
-        digit_tool.add_display(-1.0, minutes_seconds, true);
-        digit_tool.add_display(1.0 - digit_tool::DISPLAY_WIDTH, score, false);
+        digit_tool.add_display(-1.0, 0.0, minutes_seconds, true);
+        digit_tool.add_display(
+            1.0 - digit_tool::DISPLAY_WIDTH,
+            0.0,
+            score,
+            false,
+        );
 
         assert_eq!(self.vertices.len(), TOTAL_N_QUADS * 4);
     }
@@ -184,6 +250,11 @@ impl SuperPainter {
         logic: &logic::Logic,
         super_diveno: &logic::SuperDiveno,
     ) -> u32 {
+        // Once the time has run out the final tally is shown instead
+        if let Some(points) = super_diveno.points() {
+            return points;
+        }
+
         let guessed_words = super_diveno.guessed_words();
 
         match self.score_delay_time(logic) {
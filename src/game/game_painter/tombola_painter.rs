@@ -332,7 +332,7 @@ impl TombolaPainter {
             logic::Event::WordChanged => false,
             logic::Event::GridChanged => false,
             logic::Event::GuessEntered => false,
-            logic::Event::WrongGuessEntered => false,
+            logic::Event::WrongGuessEntered(_) => false,
             logic::Event::GuessRejected => false,
             logic::Event::Solved => false,
             logic::Event::ScoreChanged(_) => false,
@@ -340,8 +340,13 @@ impl TombolaPainter {
             logic::Event::CurrentPageChanged(_) => false,
             logic::Event::BingoChanged(..) => false,
             logic::Event::Bingo(..) => false,
+            logic::Event::BlackBall(_) => false,
             logic::Event::SuperDivenoToggled => false,
             logic::Event::SuperDivenoPauseToggled => false,
+            logic::Event::GuessTimerChanged => false,
+            logic::Event::GuessTimeUp => false,
+            logic::Event::StealStarted => false,
+            logic::Event::SuperDivenoTimeUp => false,
         }
     }
 
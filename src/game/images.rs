@@ -16,6 +16,8 @@
 
 use std::rc::Rc;
 use glow::HasContext;
+use super::language::Language;
+use super::letter_texture;
 
 pub struct Texture {
     id: glow::Texture,
@@ -49,10 +51,11 @@ pub struct ImageSet {
     pub bingo: Texture,
 }
 
-const N_IMAGES: usize = 6;
+// The letters aren’t loaded from a file because they are drawn with
+// a font at startup
+const N_IMAGES: usize = 5;
 
 static IMAGE_FILENAMES: [&'static str; N_IMAGES] = [
-    "letters.png",
     "segments.png",
     "balls.png",
     "tombola.png",
@@ -94,11 +97,10 @@ impl ImageLoader {
         self.n_textures += 1;
     }
 
-    pub fn complete(self) -> ImageSet {
+    pub fn complete(self, letters: Texture) -> ImageSet {
         assert_eq!(self.n_textures, N_IMAGES);
 
         let [
-            letters,
             segments,
             balls,
             tombola,
@@ -116,3 +118,55 @@ impl ImageLoader {
         }
     }
 }
+
+/// Draws the tiles for the letters of the language with the glyphs
+/// from the font data and uploads them to a texture
+pub fn create_letters_texture(
+    gl: &Rc<glow::Context>,
+    language: &Language,
+    font: &[u8],
+) -> Result<Texture, String> {
+    let atlas = letter_texture::generate(font, language.tiles())?;
+
+    let texture = unsafe {
+        Texture::new(Rc::clone(gl), gl.create_texture()?)
+    };
+
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture.id()));
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_WRAP_S,
+            glow::CLAMP_TO_EDGE as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_WRAP_T,
+            glow::CLAMP_TO_EDGE as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR_MIPMAP_NEAREST as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0, // level
+            glow::RGBA as i32,
+            atlas.width as i32,
+            atlas.height as i32,
+            0, // border
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            Some(&atlas.pixels),
+        );
+        gl.generate_mipmap(glow::TEXTURE_2D);
+    }
+
+    Ok(texture)
+}
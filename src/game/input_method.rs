@@ -0,0 +1,286 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Converts the letters typed on a keyboard without the letters of the
+// language into letters with diacritics using the rules from the
+// language profile. The conversion only depends on the letter that
+// was typed and the last letter of the word so it is shared between
+// typing a guess and typing a new word.
+
+use std::rc::Rc;
+use super::language::Language;
+
+/// How letters with diacritics are typed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputMethod {
+    /// An x after a letter adds a hat to it, eg “cx” → “ĉ”
+    XSystem,
+    /// An h after a letter adds a hat to it, eg “ch” → “ĉ”, and a u
+    /// after an a becomes “ŭ”
+    HSystem,
+    /// The letters are typed directly with a keyboard layout for the
+    /// language
+    Direct,
+}
+
+impl std::str::FromStr for InputMethod {
+    type Err = String;
+
+    /// Parses “x”, “h” or “direct”
+    fn from_str(s: &str) -> Result<InputMethod, String> {
+        match s {
+            "x" => Ok(InputMethod::XSystem),
+            "h" => Ok(InputMethod::HSystem),
+            "direct" => Ok(InputMethod::Direct),
+            _ => Err(format!("invalid input method: {}", s)),
+        }
+    }
+}
+
+/// Change to make to the word after typing a letter. The letter
+/// might not be valid in which case it should be ignored.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edit {
+    Add(char),
+    ReplaceLast(char),
+}
+
+// A conversion that can be undone with Backspace
+struct Undo {
+    // The letter that the conversion put at the end of the word
+    converted: char,
+    // The letters to type instead after removing the converted letter
+    letters: (char, Option<char>),
+}
+
+pub struct Composer {
+    language: Rc<Language>,
+    input_method: InputMethod,
+    dead_key_queued: bool,
+    // The last conversion if no other keys have been pressed since
+    last_conversion: Option<Undo>,
+}
+
+impl Composer {
+    pub fn new(
+        language: Rc<Language>,
+        input_method: InputMethod,
+    ) -> Composer {
+        Composer {
+            language,
+            input_method,
+            dead_key_queued: false,
+            last_conversion: None,
+        }
+    }
+
+    pub fn set_input_method(&mut self, input_method: InputMethod) {
+        self.input_method = input_method;
+        self.reset();
+    }
+
+    /// Forgets any pending dead key and the last conversion. This
+    /// should be called for every key that isn’t a letter or
+    /// Backspace.
+    pub fn reset(&mut self) {
+        self.dead_key_queued = false;
+        self.last_conversion = None;
+    }
+
+    /// Makes the next letter have a diacritic. This works with any
+    /// input method.
+    pub fn dead_key(&mut self) {
+        self.last_conversion = None;
+        self.dead_key_queued = true;
+    }
+
+    /// Returns how the word should be changed when a letter is typed.
+    /// `can_replace` should be false if the last letter of the word
+    /// can’t be changed.
+    pub fn type_letter(
+        &mut self,
+        last_letter: Option<char>,
+        letter: char,
+        can_replace: bool,
+    ) -> Edit {
+        self.last_conversion = None;
+
+        if std::mem::take(&mut self.dead_key_queued) {
+            return Edit::Add(
+                self.language.add_diacritic(letter).unwrap_or(letter)
+            );
+        }
+
+        let Some(last_letter) = last_letter
+        else {
+            return Edit::Add(letter);
+        };
+
+        match self.language.convert(self.input_method, last_letter, letter) {
+            Some(conversion) if conversion.keeps_last => {
+                self.last_conversion = Some(Undo {
+                    converted: conversion.converted,
+                    letters: (letter, None),
+                });
+
+                Edit::Add(conversion.converted)
+            },
+            Some(conversion) if can_replace => {
+                self.last_conversion = Some(Undo {
+                    converted: conversion.converted,
+                    letters: (last_letter, Some(letter)),
+                });
+
+                Edit::ReplaceLast(conversion.converted)
+            },
+            _ => Edit::Add(letter),
+        }
+    }
+
+    /// Returns the letters to type after removing the last letter if
+    /// Backspace should undo the last conversion, or None if Backspace
+    /// should just remove the letter.
+    pub fn backspace(
+        &mut self,
+        last_letter: Option<char>,
+    ) -> Option<impl Iterator<Item = char>> {
+        self.dead_key_queued = false;
+
+        let conversion = self.last_conversion.take()?;
+
+        let last_letter = last_letter?;
+
+        if !last_letter.to_uppercase().eq(conversion.converted.to_uppercase()) {
+            return None;
+        }
+
+        let (first, second) = conversion.letters;
+
+        Some(std::iter::once(first).chain(second))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_composer(input_method: InputMethod) -> Composer {
+        Composer::new(Rc::new(Language::default()), input_method)
+    }
+
+    // Types the letters into a word in the same way as the game,
+    // treating “<” as Backspace
+    fn type_word(composer: &mut Composer, keys: &str) -> String {
+        let language = Language::default();
+        let mut word = String::new();
+        let push_letter = |word: &mut String, letter: char| {
+            word.extend(
+                letter.to_uppercase()
+                    .filter(|&ch| language.is_valid_letter(ch))
+            );
+        };
+
+        for key in keys.chars() {
+            if key == '<' {
+                let undo = composer.backspace(word.chars().next_back())
+                    .map(|letters| letters.collect::<Vec<_>>());
+
+                word.pop();
+
+                for letter in undo.into_iter().flatten() {
+                    push_letter(&mut word, letter);
+                }
+
+                continue;
+            }
+
+            match composer.type_letter(word.chars().next_back(), key, true) {
+                Edit::Add(letter) => push_letter(&mut word, letter),
+                Edit::ReplaceLast(letter) => {
+                    word.pop();
+                    push_letter(&mut word, letter);
+                },
+            }
+        }
+
+        word
+    }
+
+    #[test]
+    fn x_system() {
+        let mut composer = make_composer(InputMethod::XSystem);
+
+        assert_eq!(type_word(&mut composer, "cxapelo"), "ĈAPELO");
+        assert_eq!(type_word(&mut composer, "SxuXto"), "ŜŬTO");
+        assert_eq!(type_word(&mut composer, "ŝuxto"), "ŜŬTO");
+        assert_eq!(type_word(&mut composer, "xaux"), "AŬ");
+        assert_eq!(type_word(&mut composer, "eux<o"), "EUO");
+        // Backspace only undoes the conversion right after it
+        assert_eq!(type_word(&mut composer, "cxa<<"), "");
+
+        assert_eq!(
+            composer.type_letter(Some('c'), 'x', false),
+            Edit::Add('x'),
+        );
+    }
+
+    #[test]
+    fn h_system() {
+        let mut composer = make_composer(InputMethod::HSystem);
+
+        assert_eq!(type_word(&mut composer, "chapelo"), "ĈAPELO");
+        assert_eq!(type_word(&mut composer, "ghhhjhsh"), "ĜĤĴŜ");
+        assert_eq!(type_word(&mut composer, "AUTO"), "AŬTO");
+        // A u after an e is left alone because it is usually a
+        // separate syllable
+        assert_eq!(type_word(&mut composer, "kreu"), "KREU");
+        assert_eq!(type_word(&mut composer, "pleu"), "PLEU");
+        assert_eq!(type_word(&mut composer, "neutrala"), "NEUTRALA");
+        assert_eq!(type_word(&mut composer, "flugh<aveno"), "FLUGHAVENO");
+        assert_eq!(type_word(&mut composer, "prau<lo"), "PRAULO");
+        assert_eq!(type_word(&mut composer, "uh"), "UH");
+        assert_eq!(type_word(&mut composer, "hx"), "H");
+
+        assert_eq!(
+            composer.type_letter(Some('c'), 'h', false),
+            Edit::Add('h'),
+        );
+        // A conversion that keeps the last letter doesn’t need to
+        // replace it
+        assert_eq!(
+            composer.type_letter(Some('a'), 'u', false),
+            Edit::Add('Ŭ'),
+        );
+    }
+
+    #[test]
+    fn direct() {
+        let mut composer = make_composer(InputMethod::Direct);
+
+        assert_eq!(type_word(&mut composer, "ĉapelo"), "ĈAPELO");
+        assert_eq!(type_word(&mut composer, "cxchau<"), "CCHA");
+
+        // Dead keys work with any input method
+        composer.dead_key();
+        assert_eq!(composer.type_letter(None, 'g', true), Edit::Add('Ĝ'));
+        composer.dead_key();
+        composer.reset();
+        assert_eq!(composer.type_letter(None, 'g', true), Edit::Add('g'));
+
+        assert_eq!("h".parse(), Ok(InputMethod::HSystem));
+        assert!("y".parse::<InputMethod>().is_err());
+    }
+}
@@ -0,0 +1,417 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// A language profile describes the alphabet of the game and the data
+// files that go with it so that the game can be played in languages
+// other than Esperanto. The profile is a text file in the data
+// directory where each line has a key followed by its value. Empty
+// lines and lines starting with # are ignored.
+
+use super::letter_texture;
+use super::input_method::InputMethod;
+
+/// Filename of the language profile to use if none is chosen
+pub const DEFAULT_LANGUAGE: &str = "eo";
+
+// Converts a letter typed after another letter into a letter with a
+// diacritic
+struct InputRule {
+    last: char,
+    typed: char,
+    converted: char,
+    // If true then the last letter is kept and the typed letter is
+    // converted. Otherwise the last letter is replaced with the
+    // converted letter.
+    keeps_last: bool,
+}
+
+/// The result of typing a letter after another with an input method
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Conversion {
+    pub converted: char,
+    pub keeps_last: bool,
+}
+
+pub struct Language {
+    // Sorted list of uppercase letters that can be used in words
+    letters: Vec<char>,
+    // Position of the tile for each letter in the tile atlas, sorted
+    // by letter
+    tiles: Vec<letter_texture::Letter>,
+    dictionary: String,
+    word_list: String,
+    // TrueType or OpenType font to draw the letter tiles with
+    font: String,
+    x_system: Vec<InputRule>,
+    h_system: Vec<InputRule>,
+    // Pairs of letters to convert after a dead key, sorted by the
+    // first letter
+    dead_key: Vec<(char, char)>,
+    esperanto_morphology: bool,
+}
+
+impl Language {
+    /// Returns the name of the file in the data directory for the
+    /// language with the given code, eg “eo”
+    pub fn filename(code: &str) -> String {
+        format!("{}.lang", code)
+    }
+
+    pub fn parse(source: &str) -> Result<Language, String> {
+        let mut letters = None;
+        let mut dictionary = None;
+        let mut word_list = None;
+        let mut font = None;
+        let mut x_system = Vec::new();
+        let mut h_system = Vec::new();
+        let mut dead_key = Vec::new();
+        let mut esperanto_morphology = false;
+
+        for (line_num, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| {
+                format!("line {}: {}", line_num + 1, message)
+            };
+
+            let (key, value) = line.split_once(char::is_whitespace)
+                .map(|(key, value)| (key, value.trim()))
+                .unwrap_or((line, ""));
+
+            match key {
+                "letters" => {
+                    let mut value = value.chars()
+                        .filter(|ch| !ch.is_whitespace())
+                        .collect::<Vec<_>>();
+
+                    if value.is_empty() {
+                        return Err(error("empty alphabet"));
+                    }
+
+                    if value.iter().any(|&ch| !is_uppercase_letter(ch)) {
+                        return Err(error(
+                            "the letters must all be uppercase"
+                        ));
+                    }
+
+                    value.sort_unstable();
+                    value.dedup();
+
+                    letters = Some(value);
+                },
+                "dictionary" => dictionary = Some(value.to_string()),
+                "word-list" => word_list = Some(value.to_string()),
+                "font" => font = Some(value.to_string()),
+                "x-system" => x_system = parse_input_rules(value)
+                    .map_err(|e| error(&e))?,
+                "h-system" => h_system = parse_input_rules(value)
+                    .map_err(|e| error(&e))?,
+                "dead-key" => dead_key = parse_dead_key_rules(value)
+                    .map_err(|e| error(&e))?,
+                "morphology" => match value {
+                    "esperanto" => esperanto_morphology = true,
+                    "none" => esperanto_morphology = false,
+                    _ => return Err(error("unknown morphology")),
+                },
+                _ => return Err(error(&format!("unknown key: {}", key))),
+            }
+        }
+
+        let Some(letters) = letters
+        else {
+            return Err("missing letters".to_string());
+        };
+
+        let missing = |key: &str| format!("missing {}", key);
+
+        let language = Language {
+            tiles: letter_texture::layout(letters.iter().copied()),
+            letters,
+            dictionary: dictionary.ok_or_else(|| missing("dictionary"))?,
+            word_list: word_list.ok_or_else(|| missing("word-list"))?,
+            font: font.ok_or_else(|| missing("font"))?,
+            x_system,
+            h_system,
+            dead_key,
+            esperanto_morphology,
+        };
+
+        // Make sure that the conversions can only create letters
+        // that can be used
+        let converted = language.x_system.iter()
+            .chain(language.h_system.iter())
+            .map(|rule| rule.converted)
+            .chain(language.dead_key.iter().map(|&(_, converted)| converted));
+
+        for ch in converted {
+            if !language.is_valid_letter(ch) {
+                return Err(format!(
+                    "the input rules use a letter that isn’t in the \
+                     alphabet: {}",
+                    ch,
+                ));
+            }
+        }
+
+        Ok(language)
+    }
+
+    /// Returns whether the uppercase letter is in the alphabet
+    pub fn is_valid_letter(&self, letter: char) -> bool {
+        self.letters.binary_search(&letter).is_ok()
+    }
+
+    /// Returns the position of every tile in the tile atlas in the
+    /// order that they are laid out
+    pub fn tiles(&self) -> &[letter_texture::Letter] {
+        &self.tiles
+    }
+
+    /// Returns the position of the letter in the tile atlas
+    pub fn tile(&self, letter: char) -> Option<&letter_texture::Letter> {
+        self.tiles.binary_search_by(|probe| probe.ch.cmp(&letter))
+            .ok()
+            .map(|index| &self.tiles[index])
+    }
+
+    pub fn dictionary(&self) -> &str {
+        &self.dictionary
+    }
+
+    pub fn word_list(&self) -> &str {
+        &self.word_list
+    }
+
+    pub fn font(&self) -> &str {
+        &self.font
+    }
+
+    /// Returns whether words should also be accepted if they are
+    /// regularly derived from an Esperanto root in the dictionary
+    pub fn uses_esperanto_morphology(&self) -> bool {
+        self.esperanto_morphology
+    }
+
+    /// Returns the conversion for typing `typed` after `last` with
+    /// the input method, if there is one. The letters can be in
+    /// either case and the converted letter is uppercase.
+    pub fn convert(
+        &self,
+        input_method: InputMethod,
+        last: char,
+        typed: char,
+    ) -> Option<Conversion> {
+        let rules = match input_method {
+            InputMethod::XSystem => &self.x_system,
+            InputMethod::HSystem => &self.h_system,
+            InputMethod::Direct => return None,
+        };
+
+        let last = to_uppercase(last);
+        let typed = to_uppercase(typed);
+
+        rules.iter()
+            .find(|rule| rule.last == last && rule.typed == typed)
+            .map(|rule| Conversion {
+                converted: rule.converted,
+                keeps_last: rule.keeps_last,
+            })
+    }
+
+    /// Returns the uppercase letter to use if the letter is typed
+    /// after a dead key
+    pub fn add_diacritic(&self, letter: char) -> Option<char> {
+        let letter = to_uppercase(letter);
+
+        self.dead_key.binary_search_by(|probe| probe.0.cmp(&letter))
+            .ok()
+            .map(|index| self.dead_key[index].1)
+    }
+
+    /// Returns whether the letter can be typed with a dead key
+    pub fn has_diacritic(&self, letter: char) -> bool {
+        let letter = to_uppercase(letter);
+
+        self.dead_key.iter().any(|&(_, converted)| converted == letter)
+    }
+}
+
+impl Default for Language {
+    /// The profile for Esperanto
+    fn default() -> Language {
+        Language::parse(include_str!("../../data/eo.lang")).unwrap()
+    }
+}
+
+fn to_uppercase(letter: char) -> char {
+    let mut upper = letter.to_uppercase();
+
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => letter,
+    }
+}
+
+fn is_uppercase_letter(letter: char) -> bool {
+    letter.is_alphabetic() && to_uppercase(letter) == letter
+}
+
+// Parses rules like “CX:Ĉ AU:AŬ”
+fn parse_input_rules(value: &str) -> Result<Vec<InputRule>, String> {
+    value.split_whitespace().map(|rule| {
+        let error = || format!("invalid input rule: {}", rule);
+
+        let (keys, converted) = rule.split_once(':').ok_or_else(error)?;
+
+        let mut keys = keys.chars();
+        let (Some(last), Some(typed), None) =
+            (keys.next(), keys.next(), keys.next())
+        else {
+            return Err(error());
+        };
+
+        let mut converted = converted.chars();
+
+        let (converted, keeps_last) =
+            match (converted.next(), converted.next(), converted.next()) {
+                (Some(converted), None, _) => (converted, false),
+                (Some(kept), Some(converted), None) if kept == last => {
+                    (converted, true)
+                },
+                _ => return Err(error()),
+            };
+
+        if ![last, typed, converted].into_iter().all(is_uppercase_letter) {
+            return Err(error());
+        }
+
+        Ok(InputRule { last, typed, converted, keeps_last })
+    }).collect()
+}
+
+// Parses rules like “C:Ĉ G:Ĝ”
+fn parse_dead_key_rules(value: &str) -> Result<Vec<(char, char)>, String> {
+    let mut rules = value.split_whitespace().map(|rule| {
+        let mut chars = rule.chars();
+
+        match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some(letter), Some(':'), Some(converted), None)
+                if is_uppercase_letter(letter)
+                && is_uppercase_letter(converted) =>
+            {
+                Ok((letter, converted))
+            },
+            _ => Err(format!("invalid dead key rule: {}", rule)),
+        }
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    rules.sort_unstable();
+
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn esperanto() {
+        let language = Language::default();
+
+        assert!(language.is_valid_letter('A'));
+        assert!(language.is_valid_letter('Ŭ'));
+        assert!(!language.is_valid_letter('a'));
+        assert!(!language.is_valid_letter('X'));
+        assert!(!language.is_valid_letter(' '));
+
+        assert_eq!(language.tile('A').unwrap().s1, 16383);
+        assert!(language.tile('.').is_some());
+        assert!(language.tile('Q').is_none());
+
+        assert_eq!(language.dictionary(), "dictionary.bin");
+        assert_eq!(language.word_list(), "wordlist.bin");
+        assert_eq!(language.font(), "DejaVuSans-Bold.ttf");
+        assert_eq!(language.tiles().len(), 30);
+        assert!(language.uses_esperanto_morphology());
+
+        assert_eq!(
+            language.convert(InputMethod::XSystem, 'c', 'x'),
+            Some(Conversion { converted: 'Ĉ', keeps_last: false }),
+        );
+        assert_eq!(
+            language.convert(InputMethod::HSystem, 'A', 'u'),
+            Some(Conversion { converted: 'Ŭ', keeps_last: true }),
+        );
+        assert!(language.convert(InputMethod::HSystem, 'c', 'x').is_none());
+        assert!(language.convert(InputMethod::Direct, 'c', 'x').is_none());
+
+        assert_eq!(language.add_diacritic('s'), Some('Ŝ'));
+        assert_eq!(language.add_diacritic('a'), None);
+        assert!(language.has_diacritic('ĝ'));
+        assert!(!language.has_diacritic('g'));
+    }
+
+    #[test]
+    fn parse() {
+        let language = Language::parse(
+            "# Nederlands\n\
+             letters ABCDEFGHIJKLMNOPQRSTUVWXYZĲ\n\
+             dictionary nl.bin\n\
+             word-list nl-words.bin\n\
+             font nl.ttf\n\
+             x-system IJ:Ĳ\n"
+        ).unwrap();
+
+        assert!(language.is_valid_letter('Ĳ'));
+        assert!(language.is_valid_letter('Q'));
+        assert!(!language.uses_esperanto_morphology());
+        assert_eq!(
+            language.convert(InputMethod::XSystem, 'i', 'j'),
+            Some(Conversion { converted: 'Ĳ', keeps_last: false }),
+        );
+        assert!(language.add_diacritic('c').is_none());
+
+        for (source, message) in [
+            ("", "missing letters"),
+            ("letters abc", "line 1: the letters must all be uppercase"),
+            ("letters ABC\nfoo bar", "line 2: unknown key: foo"),
+            ("letters ABC\ndictionary a\nword-list b", "missing font"),
+            ("letters ABC\nx-system AB", "line 2: invalid input rule: AB"),
+            ("letters ABC\nh-system AB:CB", "line 2: invalid input rule: AB:CB"),
+            ("letters ABC\ndead-key A:", "line 2: invalid dead key rule: A:"),
+            ("letters ABC\nmorphology french", "line 2: unknown morphology"),
+            (
+                "letters ABC\n\
+                 dictionary a\n\
+                 word-list b\n\
+                 font c\n\
+                 dead-key C:Ĉ",
+                "the input rules use a letter that isn’t in the alphabet: Ĉ",
+            ),
+        ] {
+            assert_eq!(
+                Language::parse(source).err().as_deref(),
+                Some(message),
+                "{}",
+                source,
+            );
+        }
+    }
+}
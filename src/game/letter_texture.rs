@@ -0,0 +1,314 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use ab_glyph::{Font, FontRef};
+
+// Letters that have a tile in the texture as well as the letters of
+// the language
+static EXTRA_LETTERS: [char; 2] = [' ', '.'];
+
+// Size in pixels of each tile in the texture
+const TILE_SIZE: usize = 128;
+// Gap between the edge of the tile and its background
+const BORDER_SIZE: f32 = TILE_SIZE as f32 / 16.0;
+// Radius of the rounded corners of the background
+const CORNER_SIZE: f32 = TILE_SIZE as f32 / 4.0;
+// Size of the font as a fraction of the background
+const FONT_SIZE: f32 = 0.8;
+// Position of the baseline as a fraction of the background
+const BASELINE: f32 = 0.82;
+// Number of samples in each direction within a pixel when working
+// out how much of it is covered by the background
+const N_SAMPLES: usize = 4;
+
+pub struct Letter {
+    pub ch: char,
+    pub s1: u16,
+    pub t1: u16,
+    pub s2: u16,
+    pub t2: u16,
+}
+
+/// Returns the number of tiles in each row and column of a texture
+/// with the given number of letters
+pub fn texture_size(n_letters: usize) -> (usize, usize) {
+    let (mut w, mut h) = (1, 1);
+
+    while w * h < n_letters {
+        if w <= h {
+            w *= 2;
+        } else {
+            h *= 2;
+        }
+    }
+
+    (w, h)
+}
+
+/// Calculates the position of each tile in the texture for the given
+/// letters. The tiles are sorted by letter and laid out in rows from
+/// the top-left of the texture.
+pub fn layout<I>(letters: I) -> Vec<Letter>
+where
+    I: IntoIterator<Item = char>
+{
+    let mut letters = letters.into_iter()
+        .chain(EXTRA_LETTERS)
+        .collect::<Vec<_>>();
+
+    letters.sort_unstable();
+    letters.dedup();
+
+    let (x_tiles, y_tiles) = texture_size(letters.len());
+
+    letters.into_iter().enumerate().map(|(letter_num, ch)| {
+        let x = letter_num % x_tiles;
+        let y = letter_num / x_tiles;
+
+        Letter {
+            ch,
+            s1: (x * 0xffff / x_tiles) as u16,
+            t1: (y * 0xffff / y_tiles) as u16,
+            s2: ((x + 1) * 0xffff / x_tiles) as u16,
+            t2: ((y + 1) * 0xffff / y_tiles) as u16,
+        }
+    }).collect()
+}
+
+/// An RGBA image containing the tiles for the letters
+pub struct Atlas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// Draws a tile for each letter with the glyphs from the TrueType or
+/// OpenType font data. The tiles must be in the order returned by
+/// [layout]. The letters are white on a green rounded rectangle so
+/// the red channel is the coverage of the glyph and the alpha channel
+/// is the coverage of the background, which is what the letter
+/// shader expects.
+pub fn generate(font_data: &[u8], tiles: &[Letter]) -> Result<Atlas, String> {
+    let font = FontRef::try_from_slice(font_data)
+        .map_err(|e| format!("error loading font: {}", e))?;
+
+    let (x_tiles, y_tiles) = texture_size(tiles.len());
+    let width = x_tiles * TILE_SIZE;
+    let height = y_tiles * TILE_SIZE;
+    let mut pixels = vec![0u8; width * height * 4];
+
+    let background = tile_background();
+
+    for (tile_num, letter) in tiles.iter().enumerate() {
+        let tile_x = tile_num % x_tiles * TILE_SIZE;
+        let tile_y = tile_num / x_tiles * TILE_SIZE;
+
+        for (y, row) in background.chunks(TILE_SIZE).enumerate() {
+            let start = ((tile_y + y) * width + tile_x) * 4;
+
+            for (pixel, &alpha) in pixels[start..start + TILE_SIZE * 4]
+                .chunks_mut(4)
+                .zip(row.iter())
+            {
+                pixel.copy_from_slice(&[0, 255, 0, alpha]);
+            }
+        }
+
+        draw_glyph(&font, letter.ch, |x, y, coverage| {
+            let pos = ((tile_y + y) * width + tile_x + x) * 4;
+            let value = (coverage * 255.0).round() as u8;
+
+            pixels[pos] = value;
+            pixels[pos + 2] = value;
+        })?;
+    }
+
+    Ok(Atlas { width, height, pixels })
+}
+
+// Returns the alpha value of each pixel in the rounded rectangle that
+// is drawn behind every letter
+fn tile_background() -> Vec<u8> {
+    let inner_min = BORDER_SIZE + CORNER_SIZE;
+    let inner_max = TILE_SIZE as f32 - BORDER_SIZE - CORNER_SIZE;
+
+    let is_inside = |x: f32, y: f32| {
+        // Distance from the rectangle without the rounded corners
+        let dx = x - x.clamp(inner_min, inner_max);
+        let dy = y - y.clamp(inner_min, inner_max);
+
+        dx * dx + dy * dy <= CORNER_SIZE * CORNER_SIZE
+    };
+
+    (0..TILE_SIZE * TILE_SIZE).map(|pixel_num| {
+        let x = (pixel_num % TILE_SIZE) as f32;
+        let y = (pixel_num / TILE_SIZE) as f32;
+
+        let n_inside = (0..N_SAMPLES * N_SAMPLES).filter(|sample| {
+            let sx = (sample % N_SAMPLES) as f32 + 0.5;
+            let sy = (sample / N_SAMPLES) as f32 + 0.5;
+
+            is_inside(x + sx / N_SAMPLES as f32, y + sy / N_SAMPLES as f32)
+        }).count();
+
+        (n_inside * 255 / (N_SAMPLES * N_SAMPLES)) as u8
+    }).collect()
+}
+
+// Rasterises the glyph for the letter centred horizontally in a tile
+// and calls `put_pixel` with the coverage for each pixel that it
+// touches within the tile
+fn draw_glyph<F>(
+    font: &FontRef,
+    letter: char,
+    mut put_pixel: F,
+) -> Result<(), String>
+where
+    F: FnMut(usize, usize, f32)
+{
+    let glyph_id = font.glyph_id(letter);
+
+    if glyph_id.0 == 0 {
+        return Err(format!("the font has no glyph for “{}”", letter));
+    }
+
+    let background_size = TILE_SIZE as f32 - BORDER_SIZE * 2.0;
+    // The scale used by ab_glyph is the height of the font rather
+    // than the size of the em square
+    let em_size = background_size * FONT_SIZE;
+    let scale = em_size * font.height_unscaled()
+        / font.units_per_em().unwrap_or(font.height_unscaled());
+    let baseline = BORDER_SIZE + background_size * BASELINE;
+
+    let Some(outline) = font.outline_glyph(
+        glyph_id.with_scale_and_position(scale, ab_glyph::point(0.0, baseline))
+    )
+    else {
+        // The glyph has no outline, eg for a space
+        return Ok(());
+    };
+
+    let bounds = outline.px_bounds();
+    let offset = (TILE_SIZE as f32 / 2.0
+                  - (bounds.min.x + bounds.max.x) / 2.0)
+        .round();
+
+    let outline = font.outline_glyph(
+        glyph_id.with_scale_and_position(
+            scale,
+            ab_glyph::point(offset, baseline),
+        )
+    ).unwrap();
+
+    let bounds = outline.px_bounds();
+
+    outline.draw(|x, y, coverage| {
+        let x = bounds.min.x as i32 + x as i32;
+        let y = bounds.min.y as i32 + y as i32;
+
+        if (0..TILE_SIZE as i32).contains(&x)
+            && (0..TILE_SIZE as i32).contains(&y)
+        {
+            put_pixel(x as usize, y as usize, coverage.min(1.0));
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn esperanto_layout() {
+        let letters = layout("ABCĈDEFGĜHĤIJĴKLMNOPRSŜTUŬVZ".chars());
+
+        assert_eq!(letters.len(), 30);
+
+        // These values were generated by create-tile-texture before
+        // the layout was calculated at runtime
+        assert_eq!(letters[0].ch, ' ');
+        assert_eq!(
+            (letters[0].s1, letters[0].t1, letters[0].s2, letters[0].t2),
+            (0, 0, 8191, 16383),
+        );
+        assert_eq!(letters[2].ch, 'A');
+        assert_eq!(
+            (letters[2].s1, letters[2].t1, letters[2].s2, letters[2].t2),
+            (16383, 0, 24575, 16383),
+        );
+        assert_eq!(letters[29].ch, 'Ŭ');
+        assert_eq!(
+            (letters[29].s1, letters[29].t1, letters[29].s2, letters[29].t2),
+            (40959, 49151, 49151, 65535),
+        );
+    }
+
+    #[test]
+    fn generate_atlas() {
+        let font = include_bytes!("../../data/DejaVuSans-Bold.ttf");
+        let letters = layout("AĈ".chars());
+
+        let atlas = generate(font, &letters).unwrap();
+
+        assert_eq!((atlas.width, atlas.height), (256, 256));
+        assert_eq!(atlas.pixels.len(), 256 * 256 * 4);
+
+        let pixel = |tile_num: usize, x: usize, y: usize| {
+            let x = tile_num % 2 * TILE_SIZE + x;
+            let y = tile_num / 2 * TILE_SIZE + y;
+            let pos = (y * atlas.width + x) * 4;
+            &atlas.pixels[pos..pos + 4]
+        };
+
+        let glyph_pixels = |tile_num: usize| {
+            (0..TILE_SIZE * TILE_SIZE).filter(|&pixel_num| {
+                pixel(tile_num, pixel_num % TILE_SIZE, pixel_num / TILE_SIZE)
+                    [0] > 0
+            }).count()
+        };
+
+        let top_row = |tile_num: usize| {
+            (0..TILE_SIZE).find(|&y| {
+                (0..TILE_SIZE).any(|x| pixel(tile_num, x, y)[0] > 0)
+            }).unwrap()
+        };
+
+        // The corners of the tiles are transparent and the middle of
+        // the space is plain green
+        assert_eq!(pixel(0, 0, 0)[3], 0);
+        assert_eq!(pixel(3, TILE_SIZE - 1, TILE_SIZE - 1)[3], 0);
+        assert_eq!(pixel(0, TILE_SIZE / 2, TILE_SIZE / 2), [0, 255, 0, 255]);
+
+        // The space has no glyph, the full stop only has a little dot
+        // and the other letters have much more
+        assert_eq!(glyph_pixels(0), 0);
+        assert!(glyph_pixels(1) > 0);
+        assert!(glyph_pixels(2) > glyph_pixels(1) * 5);
+        // The hat on the “Ĉ” is above the top of the “A”
+        assert!(top_row(3) < top_row(2));
+
+        // The “A” is centred
+        let row = (0..TILE_SIZE).filter(|&x| pixel(2, x, 90)[0] > 0)
+            .collect::<Vec<_>>();
+        let (left, right) = (row[0], row[row.len() - 1]);
+        assert!((left + right).abs_diff(TILE_SIZE) <= 2);
+
+        assert!(generate(&font[0..100], &letters).is_err());
+        assert!(generate(font, &layout(['Ω', '\u{e000}'])).is_err());
+    }
+}
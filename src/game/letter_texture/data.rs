@@ -1,218 +0,0 @@
-// Automatically generated by create_tile_texture
-
-use super::Letter;
-
-pub const N_LETTERS: usize = 30;
-
-pub static LETTERS: [Letter; N_LETTERS] = [
-    Letter {
-        ch: ' ',
-        s1: 0,
-        t1: 0,
-        s2: 8191,
-        t2: 16383,
-    },
-    Letter {
-        ch: '.',
-        s1: 8191,
-        t1: 0,
-        s2: 16383,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'A',
-        s1: 16383,
-        t1: 0,
-        s2: 24575,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'B',
-        s1: 24575,
-        t1: 0,
-        s2: 32767,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'C',
-        s1: 32767,
-        t1: 0,
-        s2: 40959,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'D',
-        s1: 40959,
-        t1: 0,
-        s2: 49151,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'E',
-        s1: 49151,
-        t1: 0,
-        s2: 57343,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'F',
-        s1: 57343,
-        t1: 0,
-        s2: 65535,
-        t2: 16383,
-    },
-    Letter {
-        ch: 'G',
-        s1: 0,
-        t1: 16383,
-        s2: 8191,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'H',
-        s1: 8191,
-        t1: 16383,
-        s2: 16383,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'I',
-        s1: 16383,
-        t1: 16383,
-        s2: 24575,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'J',
-        s1: 24575,
-        t1: 16383,
-        s2: 32767,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'K',
-        s1: 32767,
-        t1: 16383,
-        s2: 40959,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'L',
-        s1: 40959,
-        t1: 16383,
-        s2: 49151,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'M',
-        s1: 49151,
-        t1: 16383,
-        s2: 57343,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'N',
-        s1: 57343,
-        t1: 16383,
-        s2: 65535,
-        t2: 32767,
-    },
-    Letter {
-        ch: 'O',
-        s1: 0,
-        t1: 32767,
-        s2: 8191,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'P',
-        s1: 8191,
-        t1: 32767,
-        s2: 16383,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'R',
-        s1: 16383,
-        t1: 32767,
-        s2: 24575,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'S',
-        s1: 24575,
-        t1: 32767,
-        s2: 32767,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'T',
-        s1: 32767,
-        t1: 32767,
-        s2: 40959,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'U',
-        s1: 40959,
-        t1: 32767,
-        s2: 49151,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'V',
-        s1: 49151,
-        t1: 32767,
-        s2: 57343,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'Z',
-        s1: 57343,
-        t1: 32767,
-        s2: 65535,
-        t2: 49151,
-    },
-    Letter {
-        ch: 'Ĉ',
-        s1: 0,
-        t1: 49151,
-        s2: 8191,
-        t2: 65535,
-    },
-    Letter {
-        ch: 'Ĝ',
-        s1: 8191,
-        t1: 49151,
-        s2: 16383,
-        t2: 65535,
-    },
-    Letter {
-        ch: 'Ĥ',
-        s1: 16383,
-        t1: 49151,
-        s2: 24575,
-        t2: 65535,
-    },
-    Letter {
-        ch: 'Ĵ',
-        s1: 24575,
-        t1: 49151,
-        s2: 32767,
-        t2: 65535,
-    },
-    Letter {
-        ch: 'Ŝ',
-        s1: 32767,
-        t1: 49151,
-        s2: 40959,
-        t2: 65535,
-    },
-    Letter {
-        ch: 'Ŭ',
-        s1: 40959,
-        t1: 49151,
-        s2: 49151,
-        t2: 65535,
-    },
-];
@@ -14,17 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::VecDeque;
-use super::{letter_texture, random, tombola, bingo_grid, timer};
+use std::ops::Range;
+use super::{random, tombola, bingo_grid, timer, save_state};
+use super::timeout;
 use super::dictionary::Dictionary;
+use super::word_list::{self, MAX_WORD_LENGTH};
+use super::morphology;
+use super::difficulty::{self, Difficulty};
+use super::input_method::{Composer, Edit, InputMethod};
+use super::language::{self, Language};
+use std::rc::Rc;
 use tombola::Tombola;
 use bingo_grid::BingoGrid;
-
-pub const N_GUESSES: usize = 6;
-
-// Maximum time allowed in milliseconds for the super diveno
-pub const SUPER_DIVENO_TIME: i64 = 5 * 60 * 1000;
+use timeout::Timeout;
 
 const N_NUMBER_BALLS: usize = bingo_grid::N_SPACES
     - bingo_grid::N_INITIAL_SPACES_COVERED;
@@ -36,7 +40,7 @@ pub enum Event {
     WordChanged,
     GridChanged,
     GuessEntered,
-    WrongGuessEntered,
+    WrongGuessEntered(WrongGuessReason),
     GuessRejected,
     Solved,
     ScoreChanged(Team),
@@ -46,8 +50,22 @@ pub enum Event {
     BingoReset(Team),
     BingoChanged(Team, usize),
     Bingo(Team, bingo_grid::Bingo),
+    BlackBall(Team),
     SuperDivenoToggled,
     SuperDivenoPauseToggled,
+    SuperDivenoTimeUp,
+    GuessTimerChanged,
+    GuessTimeUp,
+    StealStarted,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum WrongGuessReason {
+    WrongLength,
+    NotInDictionary,
+    // The guess has already been tried. The number is the index of
+    // the earlier guess with the same word.
+    AlreadyTried(usize),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -60,12 +78,36 @@ pub enum LetterResult {
     Rejected,
 }
 
+impl LetterResult {
+    // Character used to represent the result in a save file
+    fn save_char(self) -> char {
+        match self {
+            LetterResult::Correct => 'c',
+            LetterResult::WrongPosition => 'p',
+            LetterResult::Wrong => 'w',
+            LetterResult::Rejected => 'r',
+        }
+    }
+
+    fn from_save_char(ch: char) -> Option<LetterResult> {
+        match ch {
+            'c' => Some(LetterResult::Correct),
+            'p' => Some(LetterResult::WrongPosition),
+            'w' => Some(LetterResult::Wrong),
+            'r' => Some(LetterResult::Rejected),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum Key {
     Dead,
     Backspace,
     Delete,
     Enter,
+    // Enter the guess even if it isn’t in the dictionary
+    ForceEnter,
     PageDown,
     Space,
     Home,
@@ -76,6 +118,13 @@ pub enum Key {
     Down,
     Dollar,
     Backtick,
+    Undo,
+    Redo,
+    End,
+    Insert,
+    Digit(u32),
+    // Change the band of difficulty for the next words
+    NextDifficulty,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -85,24 +134,89 @@ pub enum Page {
 }
 
 impl Page {
-    pub fn position(&self) -> usize {
+    // The bingo pages for the first half of the teams are to the left
+    // of the word page and the rest are to the right.
+    pub fn position(&self, n_teams: usize) -> usize {
         match self {
-            Page::Bingo(Team::Left) => 0,
-            Page::Word => 1,
-            Page::Bingo(Team::Right) => 2,
+            Page::Word => n_left_teams(n_teams),
+            Page::Bingo(team) => {
+                if team.is_left(n_teams) {
+                    team.0
+                } else {
+                    team.0 + 1
+                }
+            },
+        }
+    }
+
+    fn from_position(position: usize, n_teams: usize) -> Option<Page> {
+        let n_left_teams = n_left_teams(n_teams);
+
+        if position < n_left_teams {
+            Some(Page::Bingo(Team(position)))
+        } else if position == n_left_teams {
+            Some(Page::Word)
+        } else if position <= n_teams {
+            Some(Page::Bingo(Team(position - 1)))
+        } else {
+            None
         }
     }
 }
 
+#[derive(Clone)]
 pub struct Letter {
     pub letter: char,
     pub result: LetterResult,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-pub enum Team {
-    Left,
-    Right,
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Team(pub usize);
+
+impl Team {
+    /// Returns whether the team’s score and bingo page should be
+    /// displayed on the left of the word page.
+    pub fn is_left(self, n_teams: usize) -> bool {
+        self.0 < n_left_teams(n_teams)
+    }
+}
+
+/// Returns the number of teams whose pages are to the left of the
+/// word page.
+pub fn n_left_teams(n_teams: usize) -> usize {
+    n_teams.div_ceil(2)
+}
+
+/// Why a word from a custom word list couldn’t be added
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RejectReason {
+    NotInDictionary,
+    TooLong,
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RejectReason::NotInDictionary => write!(f, "not in the dictionary"),
+            RejectReason::TooLong => {
+                write!(f, "longer than {} letters", MAX_WORD_LENGTH)
+            },
+        }
+    }
+}
+
+/// A line from a custom word list that couldn’t be added
+pub struct RejectedWord {
+    pub line_num: usize,
+    pub word: String,
+    pub reason: RejectReason,
+}
+
+/// The result of loading a custom word list
+pub struct WordListReport {
+    // Number of different words that were added to the list
+    pub n_words: usize,
+    pub rejected: Vec<RejectedWord>,
 }
 
 pub enum BallType {
@@ -117,19 +231,59 @@ pub struct Ball {
     pub rotation: f32,
 }
 
+#[derive(Clone)]
 pub struct SuperDiveno {
     start_time: timer::Timer,
+    // Maximum time allowed in milliseconds
+    total_time: i64,
     // Extra time that should be added to the remaining time due to any pauses
     extra_time: i64,
     // If the game is currently paused, this will be the remaining
     // time that was available when it was paused
     pause_time: Option<i64>,
     guessed_words: u32,
+    // Once the time has run out, this will be the number of points
+    // that were given to the team for the guessed words
+    points: Option<u32>,
+    // The team that was playing when the Super Diveno started. The
+    // points are given to this team even if the turn changes.
+    team: Team,
 }
 
 impl SuperDiveno {
+    fn new(total_time: i64, team: Team) -> SuperDiveno {
+        SuperDiveno {
+            start_time: timer::Timer::new(),
+            total_time,
+            pause_time: Some(total_time),
+            extra_time: 0,
+            guessed_words: 0,
+            points: None,
+            team,
+        }
+    }
+
+    fn restore(
+        total_time: i64,
+        remaining_time: i64,
+        is_paused: bool,
+        guessed_words: u32,
+        points: Option<u32>,
+        team: Team,
+    ) -> SuperDiveno {
+        SuperDiveno {
+            start_time: timer::Timer::new(),
+            total_time,
+            pause_time: is_paused.then_some(remaining_time),
+            extra_time: remaining_time - total_time,
+            guessed_words,
+            points,
+            team,
+        }
+    }
+
     fn time_difference(&self) -> i64 {
-        SUPER_DIVENO_TIME - self.start_time.elapsed() + self.extra_time
+        self.total_time - self.start_time.elapsed() + self.extra_time
     }
 
     pub fn remaining_time(&self) -> i64 {
@@ -146,6 +300,16 @@ impl SuperDiveno {
         self.pause_time.is_some()
     }
 
+    pub fn is_time_up(&self) -> bool {
+        self.points.is_some()
+    }
+
+    /// Returns the points that were given to the team for the guessed
+    /// words, or None if the time hasn’t run out yet.
+    pub fn points(&self) -> Option<u32> {
+        self.points
+    }
+
     fn pause(&mut self) {
         if self.pause_time.is_none() {
             let remaining_time = self.remaining_time();
@@ -161,38 +325,159 @@ impl SuperDiveno {
     }
 }
 
-pub const N_TEAMS: usize = 2;
+// Maximum number of teams that can be displayed on the word page
+pub const MAX_TEAMS: usize = 6;
+
+/// What happens when a team draws a black ball from the tombola
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlackBallRule {
+    /// The black ball has no effect
+    Nothing,
+    /// The turn passes to the team after the one that drew the ball
+    EndTurn,
+    /// The team that drew the ball loses this many points
+    LosePoints(u32),
+}
+
+impl std::str::FromStr for BlackBallRule {
+    type Err = String;
+
+    /// Parses “nothing”, “end-turn” or a number of points to lose
+    fn from_str(s: &str) -> Result<BlackBallRule, String> {
+        match s {
+            "nothing" => Ok(BlackBallRule::Nothing),
+            "end-turn" => Ok(BlackBallRule::EndTurn),
+            _ => s.parse::<u32>()
+                .map(BlackBallRule::LosePoints)
+                .map_err(|_| format!("invalid black ball rule: {}", s)),
+        }
+    }
+}
+
+/// Settings that change the format of the game. The defaults are the
+/// rules that the game has always used.
+#[derive(Clone)]
+pub struct Rules {
+    /// Number of teams playing. This can be at most MAX_TEAMS.
+    pub n_teams: usize,
+    /// Number of guesses that the teams get to find a word
+    pub n_guesses: usize,
+    /// Time allowed in milliseconds for the super diveno
+    pub super_diveno_time: i64,
+    /// The score can’t be increased beyond this with the Up key
+    pub max_score: u32,
+    /// Points given to the current team for solving a word
+    pub word_points: u32,
+    /// Points given to a team for getting a bingo
+    pub bingo_points: u32,
+    /// Points added or removed with the Up and Down keys
+    pub score_step: u32,
+    /// Points given to the current team for each word guessed in the
+    /// super diveno once the time runs out
+    pub super_diveno_word_points: u32,
+    /// What happens when a black ball is drawn
+    pub black_ball: BlackBallRule,
+    /// Time allowed in milliseconds for each guess, or None if
+    /// guesses aren’t timed
+    pub guess_time: Option<i64>,
+    /// Whether the guess should be rejected and the turn should pass
+    /// to the next team when the time for a guess runs out
+    pub guess_time_up_rejects: bool,
+    /// Whether the next team gets one more guess with an extra hint
+    /// when all of the guesses have failed
+    pub steal: bool,
+    /// Whether the turn should automatically pass to the next team
+    /// after a wrong or rejected guess
+    pub auto_pass: bool,
+    /// Whether to reveal an extra letter before automatically
+    /// passing the turn
+    pub hint_on_pass: bool,
+    /// Only pick words from this band of difficulty, or None to pick
+    /// any word
+    pub word_difficulty: Option<Difficulty>,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            n_teams: 2,
+            n_guesses: 6,
+            super_diveno_time: 5 * 60 * 1000,
+            max_score: 990,
+            word_points: 50,
+            bingo_points: 100,
+            score_step: 10,
+            super_diveno_word_points: 10,
+            black_ball: BlackBallRule::Nothing,
+            guess_time: None,
+            guess_time_up_rejects: false,
+            steal: false,
+            auto_pass: false,
+            hint_on_pass: false,
+            word_difficulty: None,
+        }
+    }
+}
 
-const MAX_SCORE: u32 = 990;
+// Maximum number of actions that can be undone
+const MAX_HISTORY_LENGTH: usize = 100;
 
-static HATABLE_LETTERS: [(char, char); 12] = [
-    ('C', 'Ĉ'),
-    ('G', 'Ĝ'),
-    ('H', 'Ĥ'),
-    ('J', 'Ĵ'),
-    ('S', 'Ŝ'),
-    ('U', 'Ŭ'),
-    ('c', 'ĉ'),
-    ('g', 'ĝ'),
-    ('h', 'ĥ'),
-    ('j', 'ĵ'),
-    ('s', 'ŝ'),
-    ('u', 'ŭ'),
-];
+// Copy of the parts of the game state that can be restored with the
+// undo and redo keys
+struct HistoryEntry {
+    word: String,
+    word_length: usize,
+    in_progress_guess: String,
+    guesses: Vec<Vec<Letter>>,
+    n_guesses: usize,
+    scores: Vec<u32>,
+    current_team: Team,
+    visible_letters: u32,
+    is_solved: bool,
+    is_stealing: bool,
+    // The state of the super diveno if it is running
+    super_diveno: Option<SuperDiveno>,
+    bingo_grids: Vec<BingoGrid>,
+    // Bitmask of the balls drawn from each team’s tombola
+    drawn_balls: Vec<u64>,
+}
 
 pub struct Logic {
+    rules: Rules,
+    language: Rc<Language>,
+    // Seed that was used to create the random number generator
+    seed: u64,
+    rng: random::Rng,
     dictionary: Dictionary,
     current_page: Page,
-    word_list: Box<[u64]>,
+    // The word list sorted by the length of the words
+    word_list: Box<[String]>,
+    // Range of the word list containing the words of each length
+    word_length_ranges: HashMap<usize, Range<usize>>,
+    // Band of difficulty of each word in the word list, or an empty
+    // slice if the words aren’t picked by difficulty
+    word_difficulties: Box<[Difficulty]>,
+    // Length of the words to pick or None to pick any word
+    next_word_length: Option<usize>,
+    // Whether the last key pressed was a digit so that the next digit
+    // is added to the word length
+    typing_word_length: bool,
+    // Words from the word list that have already been picked in this
+    // session so that they won’t be picked again
+    used_words: HashSet<String>,
+    // Guesses that weren’t in the dictionary but that the host
+    // accepted anyway in this session, in the order they were
+    // accepted
+    accepted_words: Vec<String>,
     word: String,
     word_length: usize,
     in_progress_guess: String,
-    guesses: [Vec<Letter>; N_GUESSES],
+    guesses: Vec<Vec<Letter>>,
     n_guesses: usize,
-    scores: [u32; N_TEAMS],
+    scores: Vec<u32>,
     super_diveno: Option<SuperDiveno>,
-    tombolas: [Tombola; N_TEAMS],
-    bingo_grids: [BingoGrid; N_TEAMS],
+    tombolas: Vec<Tombola>,
+    bingo_grids: Vec<BingoGrid>,
     current_team: Team,
     event_queue: VecDeque<Event>,
     letter_counter: LetterCounter,
@@ -200,54 +485,204 @@ pub struct Logic {
     // either because it was given as a hint or because they guessed
     // the right letter position.
     visible_letters: u32,
-    dead_key_queued: bool,
+    // Converts the typed letters into letters with hats
+    composer: Composer,
+    // Word being typed by the host to replace the current word, or
+    // None if the host isn’t choosing a word
+    typed_word: Option<String>,
+    // Time since the current guess was started if guesses are timed
+    guess_timer: Option<timer::Timer>,
     is_solved: bool,
+    // Set when the next team has been given an extra guess to steal
+    // the word after all of the guesses failed
+    is_stealing: bool,
+    undo_history: VecDeque<HistoryEntry>,
+    redo_history: Vec<HistoryEntry>,
 }
 
 impl Logic {
-    fn new(dictionary: Dictionary, word_list: Box<[u64]>) -> Logic {
+    fn new(
+        language: Rc<Language>,
+        rules: Rules,
+        dictionary: Dictionary,
+        word_list: Box<[String]>,
+        seed: u64,
+    ) -> Logic {
+        assert!(rules.n_guesses > 0);
+        assert!(rules.n_teams > 0 && rules.n_teams <= MAX_TEAMS);
+
+        let n_teams = rules.n_teams;
+
+        // Allocate an extra guess in case the word is stolen
+        let guesses = (0..=rules.n_guesses).map(|_| Vec::new()).collect();
+
+        let (word_list, word_length_ranges) =
+            sort_words_by_length(word_list.into_vec());
+
         let mut logic = Logic {
+            rules,
+            composer: Composer::new(
+                Rc::clone(&language),
+                InputMethod::XSystem,
+            ),
+            language,
+            seed,
+            rng: random::Rng::new(seed),
             dictionary,
             current_page: Page::Word,
             word_list,
+            word_length_ranges,
+            word_difficulties: Box::new([]),
+            next_word_length: None,
+            typing_word_length: false,
+            used_words: HashSet::new(),
+            accepted_words: Vec::new(),
             word: String::new(),
             word_length: 0,
             in_progress_guess: String::new(),
-            guesses: Default::default(),
+            guesses,
             n_guesses: 0,
-            scores: Default::default(),
+            scores: vec![0; n_teams],
             super_diveno: None,
-            tombolas: [Tombola::new(N_BALLS), Tombola::new(N_BALLS)],
-            bingo_grids: Default::default(),
-            current_team: Team::Left,
+            tombolas: (0..n_teams).map(|_| Tombola::new(N_BALLS)).collect(),
+            bingo_grids: (0..n_teams).map(|_| BingoGrid::new()).collect(),
+            current_team: Team(0),
             event_queue: VecDeque::new(),
             letter_counter: LetterCounter::new(),
             visible_letters: 1,
-            dead_key_queued: false,
+            typed_word: None,
+            guess_timer: None,
             is_solved: false,
+            is_stealing: false,
+            undo_history: VecDeque::new(),
+            redo_history: Vec::new(),
         };
 
+        logic.rate_word_list();
         logic.pick_word();
 
         for bingo_grid in logic.bingo_grids.iter_mut() {
-            bingo_grid.reset();
+            bingo_grid.reset(&mut logic.rng);
         }
 
         logic
     }
 
     fn pick_word(&mut self) {
-        if !self.word_list.is_empty() {
-            let word_num = random::random_range(self.word_list.len());
-            let word = self.word_list[word_num];
+        match self.pick_unused_word() {
+            Some(word) => self.set_word(&word),
+            None => self.set_word("eraro"),
+        }
+    }
+
+    // Picks a random word with the chosen length from the word list
+    // that hasn’t been used yet. Once all of the words have been used
+    // the list starts again.
+    fn pick_unused_word(&mut self) -> Option<String> {
+        let range = self.candidate_words();
+
+        if range.is_empty() {
+            return None;
+        }
+
+        let difficulty = self.candidate_difficulty(range.clone());
+
+        let mut n_unused = self.unused_words(range.clone(), difficulty)
+            .count();
 
-            if let Some(word) = self.dictionary.extract_word(word) {
-                self.set_word(&word);
-                return;
+        if n_unused == 0 {
+            let band_words = self.band_words(range.clone(), difficulty)
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+
+            for word in band_words.iter() {
+                self.used_words.remove(word);
+            }
+
+            n_unused = band_words.len();
+        }
+
+        let word_num = self.rng.range(n_unused);
+        let word = self.unused_words(range, difficulty)
+            .nth(word_num)?
+            .to_string();
+
+        self.used_words.insert(word.clone());
+
+        Some(word)
+    }
+
+    // Returns the range of the word list to pick words from. If there
+    // are no words of the chosen length then any word can be picked.
+    fn candidate_words(&self) -> Range<usize> {
+        self.next_word_length
+            .and_then(|length| self.word_length_ranges.get(&length))
+            .cloned()
+            .unwrap_or(0..self.word_list.len())
+    }
+
+    // Returns the band of difficulty to pick words from in the range.
+    // If there are no words with the chosen difficulty then the
+    // difficulty is ignored.
+    fn candidate_difficulty(
+        &self,
+        range: Range<usize>,
+    ) -> Option<Difficulty> {
+        let difficulty = self.rules.word_difficulty?;
+
+        self.word_difficulties.get(range)?
+            .contains(&difficulty)
+            .then_some(difficulty)
+    }
+
+    fn band_words(
+        &self,
+        range: Range<usize>,
+        difficulty: Option<Difficulty>,
+    ) -> impl Iterator<Item = &str> + '_ {
+        range.filter_map(move |index| {
+            if difficulty.is_none()
+                || Some(self.word_difficulties[index]) == difficulty
+            {
+                Some(self.word_list[index].as_str())
+            } else {
+                None
             }
+        })
+    }
+
+    fn unused_words(
+        &self,
+        range: Range<usize>,
+        difficulty: Option<Difficulty>,
+    ) -> impl Iterator<Item = &str> + '_ {
+        self.band_words(range, difficulty).filter(|&word| {
+            !self.used_words.contains(word)
+        })
+    }
+
+    // Splits the words of each length in the word list into bands of
+    // difficulty if the rules pick words by difficulty
+    fn rate_word_list(&mut self) {
+        if self.rules.word_difficulty.is_none() {
+            self.word_difficulties = Box::new([]);
+            return;
+        }
+
+        let rater = difficulty::Rater::new(&self.dictionary, &self.language);
+        let mut word_difficulties =
+            vec![Difficulty::Medium; self.word_list.len()];
+
+        for range in self.word_length_ranges.values() {
+            let scores = self.word_list[range.clone()].iter()
+                .map(|word| rater.rate(word))
+                .collect::<Vec<_>>();
+
+            word_difficulties[range.clone()]
+                .copy_from_slice(&difficulty::bands(&scores));
         }
 
-        self.set_word("eraro");
+        self.word_difficulties = word_difficulties.into_boxed_slice();
     }
 
     fn set_word(&mut self, word: &str) {
@@ -259,7 +694,7 @@ impl Logic {
                 .chars()
                 .flat_map(char::to_uppercase)
                 .filter(|&c| {
-                    if is_valid_letter(c) {
+                    if self.language.is_valid_letter(c) {
                         word_length += 1;
                         true
                     } else {
@@ -275,8 +710,16 @@ impl Logic {
         self.queue_event_once(Event::GridChanged);
         self.n_guesses = 0;
         self.visible_letters = 1;
-        self.dead_key_queued = false;
+        self.composer.reset();
         self.is_solved = false;
+        self.is_stealing = false;
+        self.restart_guess_timer();
+    }
+
+    /// Changes how letters with hats are typed. The default is the
+    /// x-system.
+    pub fn set_input_method(&mut self, input_method: InputMethod) {
+        self.composer.set_input_method(input_method);
     }
 
     pub fn word(&self) -> &str {
@@ -288,50 +731,55 @@ impl Logic {
     }
 
     pub fn press_key(&mut self, key: Key) {
+        let typing_word_length = std::mem::take(&mut self.typing_word_length);
+
+        if self.current_page == Page::Word && self.press_typed_word_key(key) {
+            return;
+        }
+
         match key {
-            Key::Letter(mut letter) => {
+            Key::Letter(letter) => {
                 if self.current_page == Page::Word {
-                    if letter == 'x' || letter == 'X' {
-                        self.hatify_last_letter();
-                    } else {
-                        if self.dead_key_queued {
-                            letter = hatify(letter).unwrap_or(letter);
-                        }
-
-                        self.add_letter(letter);
-                    }
+                    self.type_letter(letter);
+                } else {
+                    self.composer.reset();
                 }
-
-                self.dead_key_queued = false;
             },
-            Key::Dead => self.dead_key_queued = true,
+            Key::Dead => self.composer.dead_key(),
             Key::Enter => {
-                self.dead_key_queued = false;
+                self.composer.reset();
                 match self.current_page {
-                    Page::Word => self.enter_guess(),
+                    Page::Word => self.enter_guess(false),
                     Page::Bingo(team) => self.spin_tombola(team),
                 }
             },
+            Key::ForceEnter => {
+                self.composer.reset();
+                if self.current_page == Page::Word {
+                    self.enter_guess(true);
+                }
+            },
             Key::Backspace => {
-                self.dead_key_queued = false;
                 if self.current_page == Page::Word {
-                    self.remove_letter();
+                    self.backspace();
+                } else {
+                    self.composer.reset();
                 }
             },
             Key::Delete => {
-                self.dead_key_queued = false;
+                self.composer.reset();
                 if self.current_page == Page::Word {
                     self.reject_guess();
                 }
             },
             Key::PageDown => {
-                self.dead_key_queued = false;
+                self.composer.reset();
                 if self.current_page == Page::Word {
                     self.add_hint();
                 }
             },
             Key::Space => {
-                self.dead_key_queued = false;
+                self.composer.reset();
 
                 match self.super_diveno.as_mut() {
                     Some(super_diveno) => {
@@ -342,61 +790,236 @@ impl Logic {
                         }
                         self.queue_event_once(Event::SuperDivenoPauseToggled);
                     },
-                    None => self.change_current_team(),
+                    None => {
+                        self.record_history();
+                        self.change_current_team();
+                    },
                 }
             },
             Key::Home => {
-                self.dead_key_queued = false;
+                self.composer.reset();
+                self.record_history();
                 match self.current_page {
                     Page::Word => self.pick_word(),
                     Page::Bingo(team) => self.reset_bingo(team),
                 }
             },
             Key::Left =>  {
-                self.dead_key_queued = false;
+                self.composer.reset();
                 self.change_page_left();
             },
             Key::Right => {
-                self.dead_key_queued = false;
+                self.composer.reset();
                 self.change_page_right();
             },
             Key::Up => {
                 if self.super_diveno.is_none() {
-                    self.add_to_score(10);
+                    self.add_to_score(self.rules.score_step as i32);
                 }
             },
             Key::Down => {
                 if self.super_diveno.is_none() {
-                    self.add_to_score(-10);
+                    self.add_to_score(-(self.rules.score_step as i32));
                 }
             },
             Key::Dollar | Key::Backtick => {
                 if self.current_page == Page::Word {
+                    self.record_history();
                     self.toggle_super_diveno();
                 }
             },
+            Key::Undo => {
+                self.composer.reset();
+                self.undo();
+            },
+            Key::Redo => {
+                self.composer.reset();
+                self.redo();
+            },
+            Key::End => {
+                self.composer.reset();
+                self.used_words.clear();
+            },
+            Key::Insert => {
+                self.composer.reset();
+                if self.current_page == Page::Word {
+                    self.typed_word = Some(String::new());
+                    self.queue_event_once(Event::GridChanged);
+                }
+            },
+            Key::Digit(digit) => {
+                self.composer.reset();
+                if self.current_page == Page::Word {
+                    self.type_word_length_digit(digit, typing_word_length);
+                }
+            },
+            Key::NextDifficulty => {
+                self.composer.reset();
+                if self.current_page == Page::Word {
+                    self.next_word_difficulty();
+                }
+            },
+        }
+    }
+
+    // Sets the length of the words to pick. A digit typed straight
+    // after another one is added to the length so that lengths with
+    // two digits can be chosen, unless that would make the length
+    // too long.
+    fn type_word_length_digit(&mut self, digit: u32, continues: bool) {
+        let digit = digit as usize;
+
+        let length = match self.next_word_length {
+            Some(length) if continues
+                && length * 10 + digit <= MAX_WORD_LENGTH =>
+            {
+                length * 10 + digit
+            },
+            _ => digit,
+        };
+
+        // Zero means that any length can be picked
+        self.next_word_length = (length > 0).then_some(length);
+        self.typing_word_length = true;
+    }
+
+    // Moves to the next band of difficulty for the words that will be
+    // picked, going back to any difficulty after the hardest one. The
+    // current word isn’t changed.
+    fn next_word_difficulty(&mut self) {
+        self.rules.word_difficulty = match self.rules.word_difficulty {
+            None => Some(Difficulty::Easy),
+            Some(Difficulty::Easy) => Some(Difficulty::Medium),
+            Some(Difficulty::Medium) => Some(Difficulty::Hard),
+            Some(Difficulty::Hard) => None,
+        };
+
+        // The words are only rated the first time a difficulty is
+        // chosen
+        if self.rules.word_difficulty.is_some()
+            && self.word_difficulties.is_empty()
+        {
+            self.rate_word_list();
+        }
+    }
+
+    // Handles a key while the host is typing a word to use as the
+    // next word. Returns false if the key should be handled normally
+    // instead.
+    fn press_typed_word_key(&mut self, key: Key) -> bool {
+        let Some(typed_word) = self.typed_word.as_mut()
+        else {
+            return false;
+        };
+
+        match key {
+            Key::Letter(letter) => {
+                match self.composer.type_letter(
+                    typed_word.chars().next_back(),
+                    letter,
+                    true,
+                ) {
+                    Edit::Add(letter) => {
+                        push_typed_letter(&self.language, typed_word, letter)
+                    },
+                    Edit::ReplaceLast(letter) => {
+                        typed_word.pop();
+                        push_typed_letter(&self.language, typed_word, letter);
+                    },
+                }
+            },
+            Key::Dead => self.composer.dead_key(),
+            Key::Backspace => {
+                let undo = self.composer.backspace(
+                    typed_word.chars().next_back()
+                );
+
+                typed_word.pop();
+
+                // Type the letters of a wrongly converted sequence again
+                for letter in undo.into_iter().flatten() {
+                    push_typed_letter(&self.language, typed_word, letter);
+                }
+            },
+            Key::Enter => {
+                self.composer.reset();
+                self.set_typed_word();
+                return true;
+            },
+            Key::Insert => {
+                // Pressing Insert again cancels choosing the word
+                self.composer.reset();
+                self.typed_word = None;
+            },
+            _ => return false,
         }
+
+        self.queue_event_once(Event::GridChanged);
+
+        true
     }
 
-    fn hatify_last_letter(&mut self) {
-        let mut last_letters = self.in_progress_guess.chars().rev();
+    // Returns whether the word is in the dictionary or, if the
+    // language uses Esperanto morphology, is derived from a word in it
+    fn is_in_dictionary(&self, word: &str) -> bool {
+        if self.language.uses_esperanto_morphology() {
+            morphology::contains(&self.dictionary, word)
+        } else {
+            self.dictionary.contains(word)
+        }
+    }
 
-        let Some(letter) = last_letters.next()
+    fn set_typed_word(&mut self) {
+        let Some(typed_word) = self.typed_word.take()
         else {
             return;
         };
 
-        // Don’t hatify the first letter
-        if last_letters.next().is_none() {
+        if !self.is_in_dictionary(&typed_word) {
+            self.typed_word = Some(typed_word);
+            self.queue_event_once(
+                Event::WrongGuessEntered(WrongGuessReason::NotInDictionary)
+            );
             return;
         }
 
-        if let Some(hatted) = hatify(letter) {
-            self.in_progress_guess.truncate(
-                self.in_progress_guess.len() - letter.len_utf8()
-            );
-            self.in_progress_guess.push(hatted);
-            self.queue_event_once(Event::GridChanged);
+        self.record_history();
+
+        // Don’t pick the word again if it’s also in the word list
+        if self.dictionary.contains(&typed_word) {
+            self.used_words.insert(typed_word.to_lowercase());
+        }
+
+        self.set_word(&typed_word);
+    }
+
+    fn type_letter(&mut self, letter: char) {
+        // Don’t replace the first letter
+        let can_replace = self.in_progress_guess.chars().nth(1).is_some();
+
+        match self.composer.type_letter(
+            self.in_progress_guess.chars().next_back(),
+            letter,
+            can_replace,
+        ) {
+            Edit::Add(letter) => self.add_letter(letter),
+            Edit::ReplaceLast(letter) => {
+                self.remove_letter();
+                self.add_letter(letter);
+            },
+        }
+    }
+
+    fn backspace(&mut self) {
+        let undo = self.composer.backspace(
+            self.in_progress_guess.chars().next_back()
+        );
+
+        self.remove_letter();
+
+        // Type the letters of a wrongly converted sequence again
+        for letter in undo.into_iter().flatten() {
+            self.add_letter(letter);
         }
     }
 
@@ -418,7 +1041,7 @@ impl Logic {
                 break;
             }
 
-            if is_valid_letter(ch) {
+            if self.language.is_valid_letter(ch) {
                 if guess_length == 0 && ch != first_letter {
                     self.in_progress_guess.push(first_letter);
                     guess_length += 1;
@@ -432,10 +1055,21 @@ impl Logic {
     }
 
     fn reject_guess(&mut self) {
-        if self.is_solved || self.n_guesses >= N_GUESSES {
+        if !self.can_reject_guess() {
             return;
         }
 
+        self.record_history();
+        self.add_rejected_guess();
+    }
+
+    fn can_reject_guess(&self) -> bool {
+        !self.is_finished() && !self.super_diveno_time_is_up()
+    }
+
+    // Adds the in-progress guess as a rejected guess without
+    // recording the history
+    fn add_rejected_guess(&mut self) {
         let guess = &mut self.guesses[self.n_guesses];
 
         guess.clear();
@@ -456,10 +1090,12 @@ impl Logic {
         self.n_guesses += 1;
         self.queue_event_once(Event::GridChanged);
         self.queue_event_once(Event::GuessRejected);
+        self.guess_failed();
+        self.restart_guess_timer();
     }
 
     fn add_hint(&mut self) {
-        if self.is_solved || self.n_guesses >= N_GUESSES {
+        if self.is_finished() {
             return;
         }
 
@@ -469,14 +1105,25 @@ impl Logic {
             return;
         }
 
+        if !self.can_reveal_letter() {
+            return;
+        }
+
+        self.record_history();
+        self.reveal_letter();
+    }
+
+    fn can_reveal_letter(&self) -> bool {
         let n_visible_letters = self.visible_letters.count_ones() as usize;
 
         // Don’t give a hint if it would reveal the entire word
-        if n_visible_letters + 1 >= self.word_length {
-            return;
-        }
+        n_visible_letters + 1 < self.word_length
+    }
+
+    fn reveal_letter(&mut self) {
+        let n_visible_letters = self.visible_letters.count_ones() as usize;
 
-        let mut letter_num = random::random_range(
+        let mut letter_num = self.rng.range(
             self.word_length - n_visible_letters
         );
 
@@ -493,104 +1140,251 @@ impl Logic {
         self.queue_event_once(Event::GridChanged);
     }
 
-    fn change_current_team(&mut self) {
-        let next_team = match self.current_team {
-            Team::Left => Team::Right,
-            Team::Right => Team::Left,
-        };
+    // Called after a guess was entered that didn’t solve the word or
+    // after a guess was rejected. This passes the turn to the next
+    // team if the rules say so.
+    fn guess_failed(&mut self) {
+        if self.super_diveno.is_some() {
+            return;
+        }
 
-        self.current_team = next_team;
-        self.queue_event_once(Event::CurrentTeamChanged);
+        if self.n_guesses >= self.rules.n_guesses {
+            self.start_steal_if_needed();
+        } else if self.rules.auto_pass {
+            if self.rules.hint_on_pass && self.can_reveal_letter() {
+                self.reveal_letter();
+            }
+
+            self.change_current_team();
+        }
     }
 
-    fn toggle_super_diveno(&mut self) {
-        match self.super_diveno {
-            None => self.super_diveno = Some(SuperDiveno {
-                start_time: timer::Timer::new(),
-                pause_time: Some(SUPER_DIVENO_TIME),
-                extra_time: 0,
-                guessed_words: 0,
-            }),
-            Some(_) => self.super_diveno = None,
+    // If all of the guesses have failed and the steal rule is
+    // enabled, this gives the next team one more guess with an extra
+    // letter revealed
+    fn start_steal_if_needed(&mut self) {
+        if !self.rules.steal || self.is_stealing {
+            return;
         }
 
-        self.queue_event_once(Event::SuperDivenoToggled);
+        self.is_stealing = true;
+
+        if self.can_reveal_letter() {
+            self.reveal_letter();
+        }
+
+        self.change_current_team();
+        self.queue_event_once(Event::StealStarted);
     }
 
-    pub fn in_progress_guess(&self) -> &str {
-        &self.in_progress_guess
+    fn change_current_team(&mut self) {
+        self.set_current_team(Team((self.current_team.0 + 1) % self.n_teams()));
     }
 
-    pub fn get_event(&mut self) -> Option<Event> {
-        self.event_queue.pop_front()
+    fn set_current_team(&mut self, team: Team) {
+        self.current_team = team;
+        self.queue_event_once(Event::CurrentTeamChanged);
+        self.restart_guess_timer();
     }
 
-    fn queue_event_once(&mut self, event: Event) {
-        if !self.event_queue.contains(&event) {
-            self.event_queue.push_back(event);
+    // Starts timing a new guess if guesses are timed and a guess can
+    // be entered. Otherwise the timer is stopped.
+    fn restart_guess_timer(&mut self) {
+        let guess_timer = (self.rules.guess_time.is_some()
+                           && self.super_diveno.is_none()
+                           && !self.is_finished())
+            .then(timer::Timer::new);
+
+        if guess_timer.is_some() || self.guess_timer.is_some() {
+            self.guess_timer = guess_timer;
+            self.queue_event_once(Event::GuessTimerChanged);
         }
     }
 
-    fn spin_tombola(&mut self, team: Team) {
-        if self.bingo_grids[team as usize].bingo().is_none() {
-            self.tombolas[team as usize].start_spin();
-            self.queue_event_once(Event::TombolaStartedSpinning(team));
-        }
+    /// Returns the time in milliseconds left for the current guess,
+    /// or None if the guess isn’t being timed.
+    pub fn guess_remaining_time(&self) -> Option<i64> {
+        let guess_time = self.rules.guess_time?;
+        let guess_timer = self.guess_timer?;
+
+        Some((guess_time - guess_timer.elapsed()).max(0))
     }
 
-    fn reset_bingo(&mut self, team: Team) {
-        self.tombolas[team as usize].reset();
-        self.bingo_grids[team as usize].reset();
-        self.queue_event_once(Event::BingoReset(team));
+    fn super_diveno_time_is_up(&self) -> bool {
+        self.super_diveno.as_ref().is_some_and(SuperDiveno::is_time_up)
+    }
+
+    /// Checks whether any timers have run out and queues the
+    /// corresponding events. This should be called again after the
+    /// delay returned by timer_timeout.
+    pub fn update_timers(&mut self) {
+        self.update_super_diveno_timer();
+        self.update_guess_timer();
     }
 
-    fn enter_guess(&mut self) {
-        if self.is_solved || self.n_guesses >= N_GUESSES {
+    fn update_super_diveno_timer(&mut self) {
+        let Some(super_diveno) = self.super_diveno.as_mut()
+        else {
             return;
-        }
+        };
 
-        if self.in_progress_guess.chars().count() != self.word_length
-            || !self.dictionary.contains(&self.in_progress_guess)
-            || self.guess_already_tried(&self.in_progress_guess)
-        {
-            self.queue_event_once(Event::WrongGuessEntered);
+        if super_diveno.is_time_up() || super_diveno.remaining_time() > 0 {
             return;
         }
 
-        self.letter_counter.clear();
+        let points = super_diveno.guessed_words
+            * self.rules.super_diveno_word_points;
 
-        let guess = &mut self.guesses[self.n_guesses];
+        super_diveno.points = Some(points);
 
-        guess.clear();
+        if points > 0 {
+            let team = super_diveno.team;
+            self.scores[team.0] += points;
+            self.queue_event_once(Event::ScoreChanged(team));
+        }
 
-        guess.extend(
-            self.in_progress_guess
-                .chars()
-                .zip(self.word.chars())
-                .map(|(letter, word_letter)| {
-                    let result = if word_letter == letter {
-                        LetterResult::Correct
-                    } else {
-                        self.letter_counter.push(word_letter);
-                        LetterResult::Wrong
-                    };
+        self.queue_event_once(Event::SuperDivenoTimeUp);
+    }
 
-                    Letter { letter, result }
-                })
-        );
+    fn update_guess_timer(&mut self) {
+        if self.guess_remaining_time() != Some(0) {
+            return;
+        }
 
-        // Add all of the correct guesses as visible letters
-        for (index, &Letter { result, .. }) in guess.iter().enumerate() {
-            if result == LetterResult::Correct {
-                self.visible_letters |= 1 << index;
+        self.guess_timer = None;
+        self.queue_event_once(Event::GuessTimerChanged);
+        self.queue_event_once(Event::GuessTimeUp);
+
+        if self.rules.guess_time_up_rejects && self.can_reject_guess() {
+            let team = self.current_team;
+
+            // Rejecting the guess and passing the turn are undone
+            // together
+            self.record_history();
+            self.add_rejected_guess();
+
+            // Rejecting the guess might have already passed the turn
+            if self.current_team == team {
+                self.change_current_team();
             }
         }
+    }
 
-        for letter in guess.iter_mut() {
-            if letter.result == LetterResult::Wrong
-                && self.letter_counter.pop(letter.letter)
+    /// Returns how long to wait before update_timers needs to be
+    /// called again.
+    pub fn timer_timeout(&self) -> Timeout {
+        let super_diveno_timeout = match self.super_diveno.as_ref() {
+            Some(super_diveno) if !super_diveno.is_paused()
+                && !super_diveno.is_time_up() =>
             {
-                letter.result = LetterResult::WrongPosition;
+                Timeout::Milliseconds(super_diveno.remaining_time())
+            },
+            _ => Timeout::Forever,
+        };
+
+        let guess_timeout = match self.guess_remaining_time() {
+            Some(remaining_time) => Timeout::Milliseconds(remaining_time),
+            None => Timeout::Forever,
+        };
+
+        super_diveno_timeout.min(guess_timeout)
+    }
+
+    fn toggle_super_diveno(&mut self) {
+        match self.super_diveno {
+            None => {
+                self.super_diveno = Some(SuperDiveno::new(
+                    self.rules.super_diveno_time,
+                    self.current_team,
+                ));
+            },
+            Some(_) => self.super_diveno = None,
+        }
+
+        self.queue_event_once(Event::SuperDivenoToggled);
+        self.restart_guess_timer();
+    }
+
+    pub fn in_progress_guess(&self) -> &str {
+        &self.in_progress_guess
+    }
+
+    /// Returns the number of letters that the host has typed while
+    /// choosing a word, or None if the host isn’t choosing a word.
+    /// The letters themselves aren’t available so that they can’t be
+    /// shown to the players.
+    pub fn typed_word_length(&self) -> Option<usize> {
+        self.typed_word.as_ref().map(|word| word.chars().count())
+    }
+
+    pub fn get_event(&mut self) -> Option<Event> {
+        self.event_queue.pop_front()
+    }
+
+    fn queue_event_once(&mut self, event: Event) {
+        if !self.event_queue.contains(&event) {
+            self.event_queue.push_back(event);
+        }
+    }
+
+    fn spin_tombola(&mut self, team: Team) {
+        if self.bingo_grids[team.0].bingo().is_none() {
+            self.tombolas[team.0].start_spin();
+            self.queue_event_once(Event::TombolaStartedSpinning(team));
+        }
+    }
+
+    fn reset_bingo(&mut self, team: Team) {
+        self.tombolas[team.0].reset();
+        self.bingo_grids[team.0].reset(&mut self.rng);
+        self.queue_event_once(Event::BingoReset(team));
+    }
+
+    // If `force` is true then the guess is accepted even if it isn’t
+    // in the dictionary and it is added to the list of accepted words
+    fn enter_guess(&mut self, force: bool) {
+        if self.is_finished() || self.super_diveno_time_is_up() {
+            return;
+        }
+
+        let in_dictionary = self.is_in_dictionary(&self.in_progress_guess);
+
+        let wrong_guess_reason =
+            if self.in_progress_guess.chars().count() != self.word_length {
+                Some(WrongGuessReason::WrongLength)
+            } else if !force && !in_dictionary {
+                Some(WrongGuessReason::NotInDictionary)
+            } else {
+                self.find_guess(&self.in_progress_guess)
+                    .map(WrongGuessReason::AlreadyTried)
+            };
+
+        if let Some(reason) = wrong_guess_reason {
+            self.queue_event_once(Event::WrongGuessEntered(reason));
+            return;
+        }
+
+        if !in_dictionary
+            && !self.accepted_words.contains(&self.in_progress_guess)
+        {
+            self.accepted_words.push(self.in_progress_guess.clone());
+        }
+
+        self.record_history();
+
+        let guess = &mut self.guesses[self.n_guesses];
+
+        score_guess(
+            &mut self.letter_counter,
+            &self.in_progress_guess,
+            &self.word,
+            guess,
+        );
+
+        // Add all of the correct guesses as visible letters
+        for (index, &Letter { result, .. }) in guess.iter().enumerate() {
+            if result == LetterResult::Correct {
+                self.visible_letters |= 1 << index;
             }
         }
 
@@ -607,7 +1401,8 @@ impl Logic {
         if self.is_solved {
             match self.super_diveno.as_mut() {
                 None => {
-                    self.scores[self.current_team as usize] += 50;
+                    self.scores[self.current_team.0] +=
+                        self.rules.word_points;
                     self.queue_event_once(
                         Event::ScoreChanged(self.current_team)
                     );
@@ -619,7 +1414,11 @@ impl Logic {
                 },
             }
             self.queue_event_once(Event::Solved);
+        } else {
+            self.guess_failed();
         }
+
+        self.restart_guess_timer();
     }
 
     fn guess_matches_word(guess: &[Letter], word: &str) -> bool {
@@ -629,23 +1428,28 @@ impl Logic {
             .all(|(a, b)| a == b)
     }
 
-    fn guess_already_tried(&self, word: &str) -> bool {
-        self.guesses().any(|guess| Logic::guess_matches_word(guess, word))
+    // Returns the index of an earlier guess with the same word
+    fn find_guess(&self, word: &str) -> Option<usize> {
+        self.guesses().position(|guess| Logic::guess_matches_word(guess, word))
     }
 
     fn change_page_left(&mut self) {
-        match self.current_page {
-            Page::Bingo(Team::Left) => (),
-            Page::Word => self.set_page(Page::Bingo(Team::Left)),
-            Page::Bingo(Team::Right) => self.set_page(Page::Word),
+        let n_teams = self.n_teams();
+        let position = self.current_page.position(n_teams);
+
+        if let Some(page) = position.checked_sub(1)
+            .and_then(|position| Page::from_position(position, n_teams))
+        {
+            self.set_page(page);
         }
     }
 
     fn change_page_right(&mut self) {
-        match self.current_page {
-            Page::Bingo(Team::Left) => self.set_page(Page::Word),
-            Page::Word => self.set_page(Page::Bingo(Team::Right)),
-            Page::Bingo(Team::Right) => (),
+        let n_teams = self.n_teams();
+        let position = self.current_page.position(n_teams);
+
+        if let Some(page) = Page::from_position(position + 1, n_teams) {
+            self.set_page(page);
         }
     }
 
@@ -666,12 +1470,12 @@ impl Logic {
 
     fn add_to_score(&mut self, diff: i32) {
         let team = self.team_to_edit();
-        let score = &mut self.scores[team as usize];
 
-        match score.checked_add_signed(diff) {
+        match self.scores[team.0].checked_add_signed(diff) {
             Some(new_score) => {
-                if new_score <= MAX_SCORE {
-                    *score = new_score;
+                if new_score <= self.rules.max_score {
+                    self.record_history();
+                    self.scores[team.0] = new_score;
                     self.queue_event_once(Event::ScoreChanged(team));
                 }
             },
@@ -691,8 +1495,15 @@ impl Logic {
         self.n_guesses
     }
 
+    /// Returns the number of guesses that can be made for the
+    /// current word. This is one more than the number in the rules
+    /// if the word can be stolen.
+    pub fn max_guesses(&self) -> usize {
+        self.rules.n_guesses + self.is_stealing as usize
+    }
+
     pub fn is_finished(&self) -> bool {
-        self.is_solved || self.n_guesses >= N_GUESSES
+        self.is_solved || self.n_guesses >= self.max_guesses()
     }
 
     pub fn is_solved(&self) -> bool {
@@ -700,17 +1511,23 @@ impl Logic {
     }
 
     pub fn team_score(&self, team: Team) -> u32 {
-        self.scores[team as usize]
+        self.scores[team.0]
     }
 
     pub fn step_tombola(&mut self, team: Team) {
-        let tombola = &mut self.tombolas[team as usize];
+        let tombola = &mut self.tombolas[team.0];
 
         tombola.step();
 
         if let Some(ball) = tombola.take_chosen_ball() {
+            // The ball has already been taken out of the tombola so
+            // record the history as if it hadn’t been drawn yet
+            let mut entry = self.history_entry();
+            entry.drawn_balls[team.0] &= !(1 << ball);
+            self.push_history_entry(entry);
+
             if ball < N_NUMBER_BALLS {
-                let bingo_grid = &self.bingo_grids[team as usize];
+                let bingo_grid = &self.bingo_grids[team.0];
 
                 let ball = bingo_grid.space_for_initial_uncovered_space_index(
                     ball
@@ -718,195 +1535,2000 @@ impl Logic {
 
                 self.queue_event_once(Event::BingoChanged(team, ball));
 
-                let bingo_grid = &mut self.bingo_grids[team as usize];
+                let bingo_grid = &mut self.bingo_grids[team.0];
 
                 if let Some(bingo) = bingo_grid.cover_space(ball) {
-                    self.scores[team as usize] += 100;
+                    self.scores[team.0] += self.rules.bingo_points;
                     self.queue_event_once(Event::ScoreChanged(team));
                     self.queue_event_once(Event::Bingo(team, bingo));
                 }
+            } else {
+                self.black_ball_drawn(team);
             }
         }
     }
 
-    pub fn balls(&self, team: Team) -> BallIter {
-        BallIter {
-            iter: self.tombolas[team as usize].balls(),
-            bingo_grid: &self.bingo_grids[team as usize],
+    fn black_ball_drawn(&mut self, team: Team) {
+        self.queue_event_once(Event::BlackBall(team));
+
+        match self.rules.black_ball {
+            BlackBallRule::Nothing => (),
+            BlackBallRule::EndTurn => {
+                let next_team = Team((team.0 + 1) % self.n_teams());
+
+                if next_team != self.current_team {
+                    self.set_current_team(next_team);
+                }
+            },
+            BlackBallRule::LosePoints(points) => {
+                let score = &mut self.scores[team.0];
+                let new_score = score.saturating_sub(points);
+
+                if new_score != *score {
+                    *score = new_score;
+                    self.queue_event_once(Event::ScoreChanged(team));
+                }
+            },
         }
     }
 
-    pub fn tombola_rotation(&self, team: Team) -> f32 {
-        self.tombolas[team as usize].rotation()
+    fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            word: self.word.clone(),
+            word_length: self.word_length,
+            in_progress_guess: self.in_progress_guess.clone(),
+            guesses: self.guesses[0..self.n_guesses].to_vec(),
+            n_guesses: self.n_guesses,
+            scores: self.scores.clone(),
+            current_team: self.current_team,
+            visible_letters: self.visible_letters,
+            is_solved: self.is_solved,
+            is_stealing: self.is_stealing,
+            super_diveno: self.super_diveno.clone(),
+            bingo_grids: self.bingo_grids.clone(),
+            drawn_balls: self.tombolas.iter().map(|tombola| {
+                tombola.drawn_balls()
+            }).collect(),
+        }
     }
 
-    pub fn tombola_is_sleeping(&self, team: Team) -> bool {
-        self.tombolas[team as usize].is_sleeping()
-    }
+    fn push_history_entry(&mut self, entry: HistoryEntry) {
+        if self.undo_history.len() >= MAX_HISTORY_LENGTH {
+            self.undo_history.pop_front();
+        }
 
-    pub fn claw_pos(&self, team: Team) -> (f32, f32) {
-        self.tombolas[team as usize].claw_pos()
-    }
+        self.undo_history.push_back(entry);
 
-    pub fn current_team(&self) -> Team {
-        self.current_team
+        // Doing a new action makes it impossible to redo the actions
+        // that were undone
+        self.redo_history.clear();
     }
 
-    pub fn current_page(&self) -> Page {
-        self.current_page
+    // Saves the current state so that the action that is about to be
+    // performed can be undone
+    fn record_history(&mut self) {
+        let entry = self.history_entry();
+        self.push_history_entry(entry);
     }
 
-    pub fn bingo_grid(&self, team: Team) -> &BingoGrid {
-        &self.bingo_grids[team as usize]
+    fn undo(&mut self) {
+        let Some(entry) = self.undo_history.pop_back()
+        else {
+            return;
+        };
+
+        self.redo_history.push(self.history_entry());
+        self.restore_history_entry(entry);
     }
 
-    pub fn super_diveno(&self) -> Option<&SuperDiveno> {
-        self.super_diveno.as_ref()
+    fn redo(&mut self) {
+        let Some(entry) = self.redo_history.pop()
+        else {
+            return;
+        };
+
+        self.undo_history.push_back(self.history_entry());
+        self.restore_history_entry(entry);
     }
-}
 
-pub struct GuessIter<'a> {
-    iter: std::slice::Iter<'a, Vec<Letter>>,
-}
+    fn restore_history_entry(&mut self, entry: HistoryEntry) {
+        if entry.word != self.word {
+            self.queue_event_once(Event::WordChanged);
+        }
 
-impl<'a> Iterator for GuessIter<'a> {
-    type Item = &'a [Letter];
+        self.word = entry.word;
+        self.word_length = entry.word_length;
+        self.in_progress_guess = entry.in_progress_guess;
+        self.n_guesses = entry.n_guesses;
+        self.visible_letters = entry.visible_letters;
+        self.is_solved = entry.is_solved;
 
-    fn next(&mut self) -> Option<&'a [Letter]> {
-        self.iter.next().map(Vec::as_slice)
-    }
-}
+        if entry.is_stealing != self.is_stealing {
+            self.is_stealing = entry.is_stealing;
+            self.queue_event_once(Event::StealStarted);
+        }
 
-impl<'a> GuessIter<'a> {
-    fn new(logic: &Logic) -> GuessIter {
-        GuessIter {
-            iter: logic.guesses[0..logic.n_guesses].iter()
+        for (guess, old_guess) in self.guesses.iter_mut().zip(entry.guesses) {
+            *guess = old_guess;
         }
-    }
-}
 
-pub struct BallIter<'a> {
-    iter: tombola::BallIter<'a>,
-    bingo_grid: &'a BingoGrid,
-}
+        self.queue_event_once(Event::GridChanged);
 
-impl<'a> Iterator for BallIter<'a> {
-    type Item = Ball;
+        if entry.current_team != self.current_team {
+            self.current_team = entry.current_team;
+            self.queue_event_once(Event::CurrentTeamChanged);
+        }
 
-    fn next(&mut self) -> Option<Ball> {
-        self.iter.next().map(|ball| {
-            let ball_type = if (ball.ball_index as usize) < N_NUMBER_BALLS {
-                let space_index =
-                    self.bingo_grid.space_for_initial_uncovered_space_index(
-                        ball.ball_index as usize
-                    );
-                let space = self.bingo_grid.space(space_index);
-                BallType::Number(space.ball as u32)
-            } else {
-                BallType::Black
-            };
+        for (team_num, score) in entry.scores.into_iter().enumerate() {
+            if score != self.scores[team_num] {
+                self.scores[team_num] = score;
+                self.queue_event_once(Event::ScoreChanged(Team(team_num)));
+            }
+        }
 
-            Ball {
-                ball_type,
-                x: ball.x,
-                y: ball.y,
-                rotation: ball.rotation,
+        match (self.super_diveno.as_mut(), entry.super_diveno) {
+            (Some(sd), Some(old_sd)) => {
+                // Keep the timer running. If this undoes the points
+                // for running out of time then update_timers will give
+                // them again based on the restored number of guessed
+                // words.
+                sd.points = old_sd.points;
+
+                if sd.guessed_words != old_sd.guessed_words {
+                    sd.guessed_words = old_sd.guessed_words;
+                    let team = sd.team;
+                    self.queue_event_once(Event::ScoreChanged(team));
+                }
+            },
+            (None, None) => (),
+            (_, old_sd) => {
+                self.super_diveno = old_sd;
+                self.queue_event_once(Event::SuperDivenoToggled);
+            },
+        }
+
+        let bingos = entry.bingo_grids.into_iter().zip(entry.drawn_balls);
+
+        for (team_num, (bingo_grid, drawn_balls)) in bingos.enumerate() {
+            let tombola = &mut self.tombolas[team_num];
+
+            if bingo_grid != self.bingo_grids[team_num]
+                || drawn_balls != tombola.drawn_balls()
+            {
+                tombola.restore_drawn_balls(drawn_balls);
+                self.bingo_grids[team_num] = bingo_grid;
+                self.queue_event_once(Event::BingoReset(Team(team_num)));
             }
-        })
+        }
+
+        self.restart_guess_timer();
     }
-}
 
-fn is_valid_letter(letter: char) -> bool {
-    let letters = &letter_texture::LETTERS;
+    /// Replaces the list of words to pick from with the words in a
+    /// text containing one word per line. Empty lines and lines
+    /// starting with # are ignored. The lines that aren’t words from
+    /// the dictionary are reported as rejected. If no words are
+    /// accepted then the word list isn’t changed. Otherwise a new
+    /// word is picked from the list.
+    pub fn load_word_list_text(&mut self, source: &str) -> WordListReport {
+        let mut words = Vec::new();
+        let mut seen_words = HashSet::new();
+        let mut rejected = Vec::new();
+
+        for (line_num, line) in source.lines().enumerate() {
+            let word = line.trim();
+
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
 
-    letters.binary_search_by(|probe| probe.ch.cmp(&letter)).is_ok()
-}
+            let reason = if word.chars().count() > MAX_WORD_LENGTH {
+                RejectReason::TooLong
+            } else if !self.dictionary.contains(word) {
+                RejectReason::NotInDictionary
+            } else {
+                let word = word.to_lowercase();
 
-fn hatify(letter: char) -> Option<char> {
-    match HATABLE_LETTERS.binary_search_by(|probe| probe.0.cmp(&letter)) {
-        Ok(index) => Some(HATABLE_LETTERS[index].1),
-        Err(_) => None,
-    }
-}
+                if seen_words.insert(word.clone()) {
+                    words.push(word);
+                }
 
-struct LetterCounter {
-    letters: HashMap<char, u32>,
-}
+                continue;
+            };
 
-impl LetterCounter {
-    fn new() -> LetterCounter {
-        LetterCounter {
-            letters: HashMap::new()
+            rejected.push(RejectedWord {
+                line_num: line_num + 1,
+                word: word.to_string(),
+                reason,
+            });
         }
-    }
 
-    fn clear(&mut self) {
-        self.letters.clear();
-    }
+        let n_words = words.len();
 
-    fn push(&mut self, letter: char) {
-        self.letters.entry(letter).and_modify(|count| *count += 1).or_insert(1);
+        if n_words > 0 {
+            let (word_list, word_length_ranges) =
+                sort_words_by_length(words);
+
+            self.word_list = word_list;
+            self.word_length_ranges = word_length_ranges;
+            self.used_words.clear();
+            self.rate_word_list();
+            self.pick_word();
+        }
+
+        WordListReport { n_words, rejected }
     }
 
-    fn pop(&mut self, letter: char) -> bool {
-        if let Some(count) = self.letters.get_mut(&letter) {
-            *count -= 1;
+    /// Returns a description of the state of the game that can be
+    /// passed to load_state to resume it later.
+    pub fn save_state(&self) -> String {
+        let mut writer = save_state::Writer::new();
 
-            if *count <= 0 {
-                self.letters.remove(&letter);
+        writer.line("teams").add(self.n_teams());
+        writer.line("current-team").add(self.current_team.0);
+        writer.line("scores").add_list(self.scores.iter());
+        writer.line("word").add(&self.word);
+
+        {
+            let mut line = writer.line("in-progress-guess");
+
+            if !self.in_progress_guess.is_empty() {
+                line.add(&self.in_progress_guess);
             }
+        }
 
-            true
-        } else {
-            false
+        writer.line("visible-letters").add(self.visible_letters);
+        writer.line("solved").add(self.is_solved as u8);
+        writer.line("stealing").add(self.is_stealing as u8);
+        writer.line("guesses").add(self.n_guesses);
+
+        for guess in self.guesses() {
+            // Rejected guesses are padded with spaces, which would
+            // split the value
+            let letters = guess.iter().map(|letter| {
+                if letter.letter == ' ' {
+                    '_'
+                } else {
+                    letter.letter
+                }
+            }).collect::<String>();
+            let results = guess.iter().map(|letter| {
+                letter.result.save_char()
+            }).collect::<String>();
+
+            writer.line("guess").add(letters).add(results);
         }
-    }
-}
 
-pub struct LogicLoader {
-    dictionary: Option<Dictionary>,
-    word_list: Option<Box<[u64]>>,
-}
+        {
+            let mut line = writer.line("super-diveno");
 
-impl LogicLoader {
-    pub fn new() -> LogicLoader {
-        LogicLoader {
-            dictionary: None,
-            word_list: None,
+            match self.super_diveno.as_ref() {
+                None => {
+                    line.add(0);
+                },
+                Some(super_diveno) => {
+                    line
+                        .add(1)
+                        .add(super_diveno.remaining_time())
+                        .add(super_diveno.is_paused() as u8)
+                        .add(super_diveno.guessed_words)
+                        .add(super_diveno.is_time_up() as u8)
+                        .add(super_diveno.points.unwrap_or(0))
+                        .add(super_diveno.team.0);
+                },
+            }
         }
-    }
 
-    pub fn next_filename(&self) -> Option<&'static str> {
-        if self.dictionary.is_none() {
-            Some("dictionary.bin")
-        } else if self.word_list.is_none() {
-            Some("wordlist.bin")
-        } else {
-            None
+        for (bingo_grid, tombola) in self.bingo_grids.iter()
+            .zip(self.tombolas.iter())
+        {
+            let mut line = writer.line("bingo");
+            bingo_grid.write_state(&mut line);
+            line.add(tombola.drawn_balls());
         }
+
+        let mut used_words = self.used_words.iter().collect::<Vec<_>>();
+        used_words.sort_unstable();
+        writer.line("used-words").add_list(used_words);
+
+        writer.line("accepted-words").add_list(self.accepted_words.iter());
+
+        writer.finish()
     }
 
-    pub fn loaded(&mut self, source: Box<[u8]>) {
-        if self.dictionary.is_none() {
-            self.dictionary = Some(Dictionary::new(source));
-        } else if self.word_list.is_none() {
-            const WORD_SIZE: usize =  std::mem::size_of::<u64>();
-            let n_words = source.len() / WORD_SIZE;
-            let mut words = Vec::<u64>::with_capacity(n_words);
-
-            for index in (0..source.len()).step_by(WORD_SIZE) {
-                let mut bytes = [0u8; WORD_SIZE];
-                bytes.copy_from_slice(&source[index..index + WORD_SIZE]);
-                words.push(u64::from_le_bytes(bytes));
-            }
+    /// Replaces the state of the game with one returned by
+    /// save_state. Nothing is changed if the state is invalid. The
+    /// number of teams can change so this should be called before
+    /// creating the painters.
+    pub fn load_state(&mut self, source: &str) -> Result<(), String> {
+        let mut reader = save_state::Reader::new(source)?;
+
+        let mut fields = reader.line("teams")?;
+        let n_teams = fields.next_number::<usize>()?;
+        if !(1..=MAX_TEAMS).contains(&n_teams) {
+            return Err(fields.error("invalid number of teams"));
+        }
+        fields.finish()?;
 
-            self.word_list = Some(words.into_boxed_slice());
+        let mut fields = reader.line("current-team")?;
+        let current_team = Team(fields.next_number()?);
+        if current_team.0 >= n_teams {
+            return Err(fields.error("invalid team"));
+        }
+        fields.finish()?;
+
+        let mut fields = reader.line("scores")?;
+        let scores = fields.next_list::<u32>()?;
+        if scores.len() != n_teams {
+            return Err(fields.error("wrong number of scores"));
+        }
+        fields.finish()?;
+
+        let mut fields = reader.line("word")?;
+        let word = fields.next_str()?.to_string();
+        let word_length = word.chars().count();
+        if word_length > MAX_WORD_LENGTH
+            || !word.chars().all(|ch| self.language.is_valid_letter(ch))
+        {
+            return Err(fields.error("invalid word"));
+        }
+        fields.finish()?;
+
+        let mut fields = reader.line("in-progress-guess")?;
+        let in_progress_guess =
+            fields.next_optional_str().unwrap_or("").to_string();
+        if in_progress_guess.chars().count() > word_length
+            || !in_progress_guess.chars().all(|ch| {
+                self.language.is_valid_letter(ch)
+            })
+        {
+            return Err(fields.error("invalid guess"));
+        }
+        fields.finish()?;
+
+        let mut fields = reader.line("visible-letters")?;
+        let visible_letters = fields.next_number::<u32>()?;
+        if visible_letters as u64 >> word_length != 0 {
+            return Err(fields.error("invalid visible letters"));
+        }
+        fields.finish()?;
+
+        let mut fields = reader.line("solved")?;
+        let is_solved = fields.next_bool()?;
+        fields.finish()?;
+
+        let is_stealing = if reader.version() >= 4 {
+            let mut fields = reader.line("stealing")?;
+            let is_stealing = fields.next_bool()?;
+            fields.finish()?;
+            is_stealing
         } else {
-            unreachable!("too many data files loaded!");
+            false
+        };
+
+        let mut fields = reader.line("guesses")?;
+        let n_guesses = fields.next_number::<usize>()?;
+        if n_guesses > self.rules.n_guesses + is_stealing as usize {
+            return Err(fields.error("too many guesses"));
         }
-    }
+        fields.finish()?;
+
+        let mut guesses = Vec::with_capacity(n_guesses);
+
+        for _ in 0..n_guesses {
+            let mut fields = reader.line("guess")?;
+            guesses.push(read_guess(&self.language, &mut fields, word_length)?);
+            fields.finish()?;
+        }
+
+        let mut fields = reader.line("super-diveno")?;
+        let super_diveno = if fields.next_bool()? {
+            let remaining_time = fields.next_number::<i64>()?.max(0);
+            let is_paused = fields.next_bool()?;
+            let guessed_words = fields.next_number()?;
+            let points = if reader.version() >= 3 {
+                let is_time_up = fields.next_bool()?;
+                let points = fields.next_number()?;
+                is_time_up.then_some(points)
+            } else if remaining_time <= 0 {
+                // Older versions didn’t give points when the time
+                // ran out
+                Some(0)
+            } else {
+                None
+            };
+            let team = if reader.version() >= 7 {
+                let team = Team(fields.next_number()?);
+                if team.0 >= n_teams {
+                    return Err(fields.error("invalid team"));
+                }
+                team
+            } else {
+                // Older versions didn’t save the team so assume it is
+                // the current one
+                current_team
+            };
+
+            Some(SuperDiveno::restore(
+                self.rules.super_diveno_time,
+                remaining_time,
+                is_paused,
+                guessed_words,
+                points,
+                team,
+            ))
+        } else {
+            None
+        };
+        fields.finish()?;
+
+        let mut bingo_grids = Vec::with_capacity(n_teams);
+        let mut drawn_balls = Vec::with_capacity(n_teams);
+
+        for _ in 0..n_teams {
+            let mut fields = reader.line("bingo")?;
+            bingo_grids.push(BingoGrid::read_state(&mut fields)?);
+            let balls = fields.next_number::<u64>()?;
+            if balls >> N_BALLS != 0 {
+                return Err(fields.error("invalid drawn balls"));
+            }
+            drawn_balls.push(balls);
+            fields.finish()?;
+        }
+
+        let used_words = if reader.version() >= 6 {
+            let mut fields = reader.line("used-words")?;
+            let used_words = fields.next_optional_list::<String>()?;
+            fields.finish()?;
+            used_words.into_iter().collect()
+        } else if reader.version() >= 2 {
+            // Older versions saved the words in the encoding of the
+            // first version of the word list format. Words that can’t
+            // be decoded can’t be picked anyway so they are ignored.
+            let mut fields = reader.line("used-words")?;
+            let used_words = fields.next_optional_list::<u64>()?;
+            fields.finish()?;
+            used_words.into_iter()
+                .filter_map(|word| self.dictionary.extract_legacy_word(word))
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let accepted_words = if reader.version() >= 5 {
+            let mut fields = reader.line("accepted-words")?;
+            let accepted_words = fields.next_optional_list::<String>()?;
+            if !accepted_words.iter().all(|word| {
+                !word.is_empty()
+                    && word.chars().all(|ch| self.language.is_valid_letter(ch))
+            }) {
+                return Err(fields.error("invalid accepted word"));
+            }
+            fields.finish()?;
+            accepted_words
+        } else {
+            Vec::new()
+        };
+
+        reader.finish()?;
+
+        self.rules.n_teams = n_teams;
+        self.used_words = used_words;
+        self.accepted_words = accepted_words;
+        self.scores.resize(n_teams, 0);
+        self.tombolas.resize_with(n_teams, || Tombola::new(N_BALLS));
+        self.bingo_grids.resize_with(n_teams, BingoGrid::new);
+
+        if let Page::Bingo(team) = self.current_page {
+            if team.0 >= n_teams {
+                self.set_page(Page::Word);
+            }
+        }
+
+        if self.super_diveno.is_some() != super_diveno.is_some() {
+            self.queue_event_once(Event::SuperDivenoToggled);
+        }
+
+        self.super_diveno = super_diveno;
+        self.composer.reset();
+        self.typed_word = None;
+        self.undo_history.clear();
+        self.redo_history.clear();
+
+        self.restore_history_entry(HistoryEntry {
+            word,
+            word_length,
+            in_progress_guess,
+            guesses,
+            n_guesses,
+            scores,
+            current_team,
+            visible_letters,
+            is_solved,
+            is_stealing,
+            super_diveno: self.super_diveno.clone(),
+            bingo_grids,
+            drawn_balls,
+        });
+
+        Ok(())
+    }
+
+    pub fn balls(&self, team: Team) -> BallIter {
+        BallIter {
+            iter: self.tombolas[team.0].balls(),
+            bingo_grid: &self.bingo_grids[team.0],
+        }
+    }
+
+    pub fn tombola_rotation(&self, team: Team) -> f32 {
+        self.tombolas[team.0].rotation()
+    }
+
+    pub fn tombola_is_sleeping(&self, team: Team) -> bool {
+        self.tombolas[team.0].is_sleeping()
+    }
+
+    pub fn claw_pos(&self, team: Team) -> (f32, f32) {
+        self.tombolas[team.0].claw_pos()
+    }
+
+    /// Returns all of the words in the dictionary that could be the
+    /// current word given the results of the guesses so far and the
+    /// letters that are visible. The words are in uppercase.
+    ///
+    /// Only the entries of the dictionary are listed. Forms that are
+    /// accepted because they are derived from a dictionary word with
+    /// the Esperanto morphology rules aren’t generated, so the answer
+    /// might be missing from the list if it is one of those.
+    pub fn possible_words(&self) -> Vec<String> {
+        // Rejected guesses don’t give any information
+        let guesses = self.guesses()
+            .filter(|guess| {
+                guess.iter().all(|l| l.result != LetterResult::Rejected)
+            })
+            .collect::<Vec<_>>();
+
+        let visible_letters = self.word.chars()
+            .enumerate()
+            .map(|(index, letter)| {
+                (self.visible_letters & (1 << index) != 0).then_some(letter)
+            })
+            .collect::<Vec<_>>();
+
+        let mut letter_counter = LetterCounter::new();
+        let mut results = Vec::new();
+        let mut words = Vec::new();
+
+        let found_words = self.dictionary.search(
+            Some(self.word_length),
+            |pos, letter| {
+                let letter = letter.to_uppercase().next().unwrap_or(letter);
+
+                // Each guess tells us whether the letter at this
+                // position is the same as the guessed letter
+                visible_letters[pos].unwrap_or(letter) == letter
+                    && guesses.iter().all(|guess| {
+                        let guess_letter = &guess[pos];
+
+                        (guess_letter.result == LetterResult::Correct)
+                            == (guess_letter.letter == letter)
+                    })
+            },
+        );
+
+        for found_word in found_words {
+            let word = found_word.word.to_uppercase();
+
+            // Score each guess against the word to check the letters
+            // in the wrong position
+            let consistent = guesses.iter().all(|guess| {
+                let guess_word = guess.iter()
+                    .map(|l| l.letter)
+                    .collect::<String>();
+
+                score_guess(
+                    &mut letter_counter,
+                    &guess_word,
+                    &word,
+                    &mut results,
+                );
+
+                results.iter()
+                    .zip(guess.iter())
+                    .all(|(a, b)| a.result == b.result)
+            });
+
+            if consistent {
+                words.push(word);
+            }
+        }
+
+        words
+    }
+
+    /// Returns the guesses that weren’t in the dictionary but that
+    /// were accepted anyway with the force enter key
+    pub fn accepted_words(&self) -> &[String] {
+        &self.accepted_words
+    }
+
+    pub fn current_team(&self) -> Team {
+        self.current_team
+    }
+
+    pub fn current_page(&self) -> Page {
+        self.current_page
+    }
+
+    pub fn bingo_grid(&self, team: Team) -> &BingoGrid {
+        &self.bingo_grids[team.0]
+    }
+
+    /// Returns the band of difficulty that new words are picked from,
+    /// or None if any word can be picked.
+    pub fn word_difficulty(&self) -> Option<Difficulty> {
+        self.rules.word_difficulty
+    }
+
+    pub fn super_diveno(&self) -> Option<&SuperDiveno> {
+        self.super_diveno.as_ref()
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn n_teams(&self) -> usize {
+        self.rules.n_teams
+    }
+
+    /// Returns the seed for the random number generator. Starting a
+    /// new game with the same seed will replay the same words, hints
+    /// and bingo grids.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+pub struct GuessIter<'a> {
+    iter: std::slice::Iter<'a, Vec<Letter>>,
+}
+
+impl<'a> Iterator for GuessIter<'a> {
+    type Item = &'a [Letter];
+
+    fn next(&mut self) -> Option<&'a [Letter]> {
+        self.iter.next().map(Vec::as_slice)
+    }
+}
+
+impl<'a> GuessIter<'a> {
+    fn new(logic: &Logic) -> GuessIter {
+        GuessIter {
+            iter: logic.guesses[0..logic.n_guesses].iter()
+        }
+    }
+}
+
+pub struct BallIter<'a> {
+    iter: tombola::BallIter<'a>,
+    bingo_grid: &'a BingoGrid,
+}
+
+impl<'a> Iterator for BallIter<'a> {
+    type Item = Ball;
+
+    fn next(&mut self) -> Option<Ball> {
+        self.iter.next().map(|ball| {
+            let ball_type = if (ball.ball_index as usize) < N_NUMBER_BALLS {
+                let space_index =
+                    self.bingo_grid.space_for_initial_uncovered_space_index(
+                        ball.ball_index as usize
+                    );
+                let space = self.bingo_grid.space(space_index);
+                BallType::Number(space.ball as u32)
+            } else {
+                BallType::Black
+            };
+
+            Ball {
+                ball_type,
+                x: ball.x,
+                y: ball.y,
+                rotation: ball.rotation,
+            }
+        })
+    }
+}
+
+// Adds a letter to the word typed by the host to replace the current
+// word if it is valid and the word isn’t too long
+fn push_typed_letter(
+    language: &Language,
+    typed_word: &mut String,
+    letter: char,
+) {
+    for ch in letter.to_uppercase() {
+        if language.is_valid_letter(ch)
+            && typed_word.chars().count() < MAX_WORD_LENGTH
+        {
+            typed_word.push(ch);
+        }
+    }
+}
+
+// Sorts the word list so that words of the same length are together
+// and returns the range of the list for each length
+fn sort_words_by_length(
+    mut word_list: Vec<String>,
+) -> (Box<[String]>, HashMap<usize, Range<usize>>) {
+    word_list.sort_by_cached_key(|word| word.chars().count());
+
+    let mut ranges = HashMap::<usize, Range<usize>>::new();
+
+    for (index, word) in word_list.iter().enumerate() {
+        ranges.entry(word.chars().count())
+            .and_modify(|range| range.end = index + 1)
+            .or_insert(index..index + 1);
+    }
+
+    (word_list.into_boxed_slice(), ranges)
+}
+
+fn read_guess(
+    language: &Language,
+    fields: &mut save_state::Fields,
+    word_length: usize,
+) -> Result<Vec<Letter>, String> {
+    let letters = fields.next_str()?;
+    let results = fields.next_str()?;
+
+    let guess = letters.chars().zip(results.chars()).map(|(letter, result)| {
+        let letter = if letter == '_' {
+            ' '
+        } else if language.is_valid_letter(letter) {
+            letter
+        } else {
+            return None;
+        };
+
+        LetterResult::from_save_char(result).map(|result| {
+            Letter { letter, result }
+        })
+    }).collect::<Option<Vec<Letter>>>();
+
+    match guess {
+        Some(guess) if guess.len() == word_length
+            && results.chars().count() == word_length =>
+        {
+            Ok(guess)
+        },
+        _ => Err(fields.error("invalid guess")),
+    }
+}
+
+// Compares the guess with the word and fills `results` with a letter
+// for each letter of the guess with its result
+fn score_guess(
+    letter_counter: &mut LetterCounter,
+    guess: &str,
+    word: &str,
+    results: &mut Vec<Letter>,
+) {
+    letter_counter.clear();
+    results.clear();
+
+    results.extend(
+        guess
+            .chars()
+            .zip(word.chars())
+            .map(|(letter, word_letter)| {
+                let result = if word_letter == letter {
+                    LetterResult::Correct
+                } else {
+                    letter_counter.push(word_letter);
+                    LetterResult::Wrong
+                };
+
+                Letter { letter, result }
+            })
+    );
+
+    for letter in results.iter_mut() {
+        if letter.result == LetterResult::Wrong
+            && letter_counter.pop(letter.letter)
+        {
+            letter.result = LetterResult::WrongPosition;
+        }
+    }
+}
+
+struct LetterCounter {
+    letters: HashMap<char, u32>,
+}
+
+impl LetterCounter {
+    fn new() -> LetterCounter {
+        LetterCounter {
+            letters: HashMap::new()
+        }
+    }
+
+    fn clear(&mut self) {
+        self.letters.clear();
+    }
+
+    fn push(&mut self, letter: char) {
+        self.letters.entry(letter).and_modify(|count| *count += 1).or_insert(1);
+    }
+
+    fn pop(&mut self, letter: char) -> bool {
+        if let Some(count) = self.letters.get_mut(&letter) {
+            *count -= 1;
+
+            if *count <= 0 {
+                self.letters.remove(&letter);
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub struct LogicLoader {
+    language_code: String,
+    language: Option<Rc<Language>>,
+    dictionary: Option<Dictionary>,
+    word_list: Option<Box<[String]>>,
+    seed: Option<u64>,
+}
+
+impl LogicLoader {
+    pub fn new() -> LogicLoader {
+        LogicLoader {
+            language_code: language::DEFAULT_LANGUAGE.to_string(),
+            language: None,
+            dictionary: None,
+            word_list: None,
+            seed: None,
+        }
+    }
+
+    pub fn next_filename(&self) -> Option<String> {
+        let Some(language) = self.language.as_ref()
+        else {
+            return Some(Language::filename(&self.language_code));
+        };
+
+        if self.dictionary.is_none() {
+            Some(language.dictionary().to_string())
+        } else if self.word_list.is_none() {
+            Some(language.word_list().to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn loaded(&mut self, source: Box<[u8]>) -> Result<(), String> {
+        if self.language.is_none() {
+            let filename = Language::filename(&self.language_code);
+
+            let source = std::str::from_utf8(&source).map_err(|e| {
+                format!("{}: {}", filename, e)
+            })?;
+
+            let language = Language::parse(source).map_err(|e| {
+                format!("{}: {}", filename, e)
+            })?;
+
+            self.language = Some(Rc::new(language));
+        } else if self.dictionary.is_none() {
+            self.dictionary = Some(Dictionary::new(source));
+        } else if self.word_list.is_none() {
+            let words = word_list::decode(
+                self.dictionary.as_ref().unwrap(),
+                &source,
+            ).map_err(|e| {
+                format!("{}: {}", self.language.as_ref().unwrap().word_list(), e)
+            })?;
+
+            self.word_list = Some(words.into_boxed_slice());
+        } else {
+            unreachable!("too many data files loaded!");
+        }
+
+        Ok(())
+    }
+
+    // Sets the code of the language profile to load, eg “eo”. This
+    // must be called before any files are loaded.
+    pub fn set_language(&mut self, code: &str) {
+        assert!(self.language.is_none());
+        self.language_code = code.to_string();
+    }
+
+    // Returns the language profile once it has been loaded. This is
+    // the first file to be loaded so that the names of the other
+    // files can be found.
+    pub fn language(&self) -> Option<&Rc<Language>> {
+        self.language.as_ref()
+    }
+
+    // Sets the seed to use for the random number generator. If this
+    // isn’t called then a random seed will be picked.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    pub fn complete(self, rules: Rules) -> Logic {
+        Logic::new(
+            self.language.unwrap(),
+            rules,
+            self.dictionary.unwrap(),
+            self.word_list.unwrap(),
+            self.seed.unwrap_or_else(random::random_seed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary;
+
+    fn make_logic(rules: Rules) -> Logic {
+        Logic::new(
+            Rc::new(Language::default()),
+            rules,
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        )
+    }
+
+    #[test]
+    fn page_position() {
+        assert_eq!(Page::Bingo(Team(0)).position(2), 0);
+        assert_eq!(Page::Word.position(2), 1);
+        assert_eq!(Page::Bingo(Team(1)).position(2), 2);
+
+        assert_eq!(Page::Bingo(Team(0)).position(3), 0);
+        assert_eq!(Page::Bingo(Team(1)).position(3), 1);
+        assert_eq!(Page::Word.position(3), 2);
+        assert_eq!(Page::Bingo(Team(2)).position(3), 3);
+
+        for n_teams in 1..=MAX_TEAMS {
+            for position in 0..=n_teams {
+                let page = Page::from_position(position, n_teams).unwrap();
+                assert_eq!(page.position(n_teams), position);
+            }
+
+            assert!(Page::from_position(n_teams + 1, n_teams).is_none());
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        assert_eq!(logic.word(), "ERARO");
+
+        // Undoing without any history shouldn’t do anything
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.n_guesses(), 0);
+
+        for ch in "ero".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        logic.press_key(Key::Delete);
+        logic.press_key(Key::Space);
+        logic.press_key(Key::Up);
+
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.current_team(), Team(1));
+        assert_eq!(logic.team_score(Team(1)), 10);
+
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.team_score(Team(1)), 0);
+        assert_eq!(logic.current_team(), Team(1));
+
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.n_guesses(), 1);
+
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.n_guesses(), 0);
+        assert_eq!(logic.in_progress_guess(), "ERO");
+
+        logic.press_key(Key::Redo);
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.in_progress_guess(), "");
+
+        logic.press_key(Key::Redo);
+        assert_eq!(logic.current_team(), Team(1));
+
+        // A new action should clear the redo history
+        logic.press_key(Key::Home);
+        logic.press_key(Key::Redo);
+        assert_eq!(logic.team_score(Team(1)), 0);
+        assert_eq!(logic.n_guesses(), 0);
+
+        while logic.get_event().is_some() {}
+
+        logic.press_key(Key::Undo);
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.current_team(), Team(0));
+
+        let mut events = Vec::new();
+        while let Some(event) = logic.get_event() {
+            events.push(event);
+        }
+        assert!(events.contains(&Event::GridChanged));
+        assert!(events.contains(&Event::CurrentTeamChanged));
+        assert!(!events.contains(&Event::WordChanged));
+    }
+
+    #[test]
+    fn undo_super_diveno() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules { super_diveno_time: 0, ..Default::default() },
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        logic.press_key(Key::Dollar);
+        logic.super_diveno.as_mut().unwrap().guessed_words = 3;
+        logic.update_timers();
+        assert_eq!(logic.team_score(Team(0)), 30);
+
+        while logic.get_event().is_some() {}
+
+        // Undoing the toggle stops the super diveno and takes back
+        // the points
+        logic.press_key(Key::Undo);
+        assert!(logic.super_diveno().is_none());
+        assert_eq!(logic.team_score(Team(0)), 0);
+
+        let mut events = Vec::new();
+        while let Some(event) = logic.get_event() {
+            events.push(event);
+        }
+        assert!(events.contains(&Event::SuperDivenoToggled));
+        assert!(events.contains(&Event::ScoreChanged(Team(0))));
+
+        // Redoing brings back the finished super diveno without
+        // giving the points twice
+        logic.press_key(Key::Redo);
+        let super_diveno = logic.super_diveno().unwrap();
+        assert_eq!(super_diveno.guessed_words(), 3);
+        assert_eq!(super_diveno.points(), Some(30));
+        assert_eq!(logic.team_score(Team(0)), 30);
+        logic.update_timers();
+        assert_eq!(logic.team_score(Team(0)), 30);
+
+        // Turning the super diveno off can be undone too
+        logic.press_key(Key::Dollar);
+        assert!(logic.super_diveno().is_none());
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.super_diveno().unwrap().points(), Some(30));
+        logic.update_timers();
+        assert_eq!(logic.team_score(Team(0)), 30);
+    }
+
+    #[test]
+    fn hints_with_seed() {
+        let hints_for_seed = |seed| {
+            let mut logic = Logic::new(
+                Rc::new(Language::default()),
+                Rules::default(),
+                Dictionary::new(Box::new([])),
+                Box::new([]),
+                seed,
+            );
+
+            (0..3).map(|_| {
+                logic.press_key(Key::PageDown);
+                logic.visible_letters()
+            }).collect::<Vec<u32>>()
+        };
+
+        for seed in 0..20 {
+            let hints = hints_for_seed(seed);
+
+            assert_eq!(hints, hints_for_seed(seed));
+
+            // The first letter is always visible and the last hint
+            // isn’t given because it would reveal the whole word
+            assert_eq!(hints[0].count_ones(), 2);
+            assert_eq!(hints[1].count_ones(), 3);
+            assert_eq!(hints[2].count_ones(), 4);
+            assert_eq!(hints[0] & 1, 1);
+            assert_eq!(hints[0] & !hints[1], 0);
+            assert_eq!(hints[1] & !hints[2], 0);
+        }
+
+        assert!((0..20).any(|seed| hints_for_seed(seed) != hints_for_seed(0)));
+    }
+
+    #[test]
+    fn unused_words() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new([])),
+            Box::new(["a", "b", "c"].map(str::to_string)),
+            0,
+        );
+
+        // Logic::new has already picked a word
+        let mut words = vec![logic.used_words.iter().next().unwrap().clone()];
+        words.push(logic.pick_unused_word().unwrap());
+        words.push(logic.pick_unused_word().unwrap());
+        words.sort_unstable();
+
+        assert_eq!(words, ["a", "b", "c"]);
+
+        // Once all of the words are used it should start again
+        assert!(logic.pick_unused_word().is_some());
+        assert_eq!(logic.used_words.len(), 1);
+
+        logic.press_key(Key::End);
+        assert!(logic.used_words.is_empty());
+    }
+
+    #[test]
+    fn word_length() {
+        let word_list = Box::new(
+            ["apple", "a", "app", "ĉapelo", "b", "c"].map(str::to_string)
+        );
+
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            word_list,
+            0,
+        );
+
+        assert_eq!(
+            &*logic.word_list,
+            ["a", "b", "c", "app", "apple", "ĉapelo"],
+        );
+        assert_eq!(logic.word_length_ranges.len(), 4);
+        assert_eq!(logic.word_length_ranges[&1], 0..3);
+        assert_eq!(logic.word_length_ranges[&3], 3..4);
+        assert_eq!(logic.word_length_ranges[&5], 4..5);
+        assert_eq!(logic.word_length_ranges[&6], 5..6);
+
+        logic.press_key(Key::Digit(5));
+        assert_eq!(logic.next_word_length, Some(5));
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word(), "APPLE");
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word(), "APPLE");
+
+        logic.press_key(Key::Digit(6));
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word(), "ĈAPELO");
+
+        // There are no words of this length so any word can be picked
+        logic.press_key(Key::Digit(2));
+        logic.press_key(Key::Home);
+        assert!(logic.word_length() > 0);
+
+        logic.press_key(Key::Digit(1));
+        for _ in 0..3 {
+            logic.press_key(Key::Home);
+            assert_eq!(logic.word_length(), 1);
+        }
+
+        logic.press_key(Key::Digit(0));
+        assert_eq!(logic.next_word_length, None);
+
+        // Digits typed one after another make a longer length
+        logic.press_key(Key::Digit(1));
+        logic.press_key(Key::Digit(2));
+        assert_eq!(logic.next_word_length, Some(12));
+        logic.press_key(Key::Digit(3));
+        logic.press_key(Key::Digit(0));
+        assert_eq!(logic.next_word_length, Some(30));
+        // A digit that would make the length too long starts again
+        logic.press_key(Key::Digit(5));
+        assert_eq!(logic.next_word_length, Some(5));
+
+        // Any other key ends the length
+        logic.press_key(Key::Home);
+        logic.press_key(Key::Digit(6));
+        assert_eq!(logic.next_word_length, Some(6));
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word(), "ĈAPELO");
+    }
+
+    #[test]
+    fn word_difficulty() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules {
+                word_difficulty: Some(Difficulty::Hard),
+                ..Default::default()
+            },
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new(
+                ["apple", "a", "app", "ĉapelo", "b", "c"].map(str::to_string)
+            ),
+            0,
+        );
+
+        assert_eq!(
+            &*logic.word_difficulties,
+            [
+                Difficulty::Easy,
+                Difficulty::Medium,
+                Difficulty::Hard,
+                Difficulty::Easy,
+                Difficulty::Easy,
+                Difficulty::Easy,
+            ],
+        );
+
+        // “c” is the only hard word with one letter, so it should be
+        // picked every time
+        logic.press_key(Key::Digit(1));
+        for _ in 0..3 {
+            logic.press_key(Key::Home);
+            assert_eq!(logic.word(), "C");
+        }
+
+        // There are no hard words with five letters so the difficulty
+        // is ignored
+        logic.press_key(Key::Digit(5));
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word(), "APPLE");
+
+        // Loading a new word list rates the new words
+        logic.load_word_list_text("a\nc\nĉapelo\n");
+        assert_eq!(
+            &*logic.word_difficulties,
+            [Difficulty::Easy, Difficulty::Medium, Difficulty::Easy],
+        );
+        logic.press_key(Key::Digit(1));
+        logic.press_key(Key::Home);
+        assert!(["A", "C"].contains(&logic.word()));
+    }
+
+    #[test]
+    fn next_word_difficulty() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new(
+                ["apple", "a", "app", "ĉapelo", "b", "c"].map(str::to_string)
+            ),
+            0,
+        );
+
+        // The words aren’t rated until a difficulty is chosen
+        assert!(logic.word_difficulties.is_empty());
+
+        logic.press_key(Key::Digit(1));
+        logic.press_key(Key::Home);
+        let word = logic.word().to_string();
+
+        logic.press_key(Key::NextDifficulty);
+        assert_eq!(logic.word_difficulty(), Some(Difficulty::Easy));
+        assert_eq!(logic.word_difficulties.len(), 6);
+        logic.press_key(Key::NextDifficulty);
+        assert_eq!(logic.word_difficulty(), Some(Difficulty::Medium));
+        logic.press_key(Key::NextDifficulty);
+        assert_eq!(logic.word_difficulty(), Some(Difficulty::Hard));
+
+        // Changing the difficulty doesn’t change the current word
+        assert_eq!(logic.word(), word);
+
+        // The next words come from the hard band
+        for _ in 0..3 {
+            logic.press_key(Key::Home);
+            assert_eq!(logic.word(), "C");
+        }
+
+        // After the hardest band any word can be picked again
+        logic.press_key(Key::NextDifficulty);
+        assert_eq!(logic.word_difficulty(), None);
+        logic.press_key(Key::Home);
+        assert_eq!(logic.word_length(), 1);
+    }
+
+    #[test]
+    fn custom_word_list() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        );
+
+        let report = logic.load_word_list_text(
+            "apple\n\n# A comment\n  Ĉapelo \nbanana\nAPPLE\nc\n"
+        );
+
+        assert_eq!(report.n_words, 3);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line_num, 5);
+        assert_eq!(report.rejected[0].word, "banana");
+        assert_eq!(
+            report.rejected[0].reason,
+            RejectReason::NotInDictionary,
+        );
+
+        assert_eq!(&*logic.word_list, ["c", "apple", "ĉapelo"]);
+        assert!(["C", "APPLE", "ĈAPELO"].contains(&logic.word()));
+
+        // A list without any valid words shouldn’t replace the list
+        let report = logic.load_word_list_text("banana\n");
+        assert_eq!(report.n_words, 0);
+        assert_eq!(logic.word_list.len(), 3);
+
+        // A dictionary with a word that has one letter too many
+        let mut data = vec![0, 1, b'*'];
+
+        for _ in 0..=MAX_WORD_LENGTH {
+            data.extend_from_slice(&[0, 1, b'a']);
+        }
+
+        data.extend_from_slice(&[0, 0, 0]);
+
+        logic.dictionary = Dictionary::new(data.into_boxed_slice());
+
+        let word = "a".repeat(MAX_WORD_LENGTH + 1);
+        assert!(logic.dictionary.contains(&word));
+
+        let report = logic.load_word_list_text(&word);
+        assert_eq!(report.n_words, 0);
+        assert_eq!(report.rejected[0].reason, RejectReason::TooLong);
+    }
+
+    #[test]
+    fn typed_word() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        );
+
+        assert_eq!(logic.typed_word_length(), None);
+
+        logic.press_key(Key::Insert);
+        assert_eq!(logic.typed_word_length(), Some(0));
+
+        for ch in "banana".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        assert_eq!(logic.typed_word_length(), Some(6));
+        assert_eq!(logic.in_progress_guess(), "");
+
+        while logic.get_event().is_some() {}
+
+        // Words that aren’t in the dictionary should be refused
+        logic.press_key(Key::Enter);
+        assert!(
+            logic.get_event() == Some(
+                Event::WrongGuessEntered(WrongGuessReason::NotInDictionary)
+            )
+        );
+        assert_eq!(logic.word(), "ERARO");
+        assert_eq!(logic.typed_word_length(), Some(6));
+
+        for _ in 0..6 {
+            logic.press_key(Key::Backspace);
+        }
+        for ch in "cxapelo".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        logic.press_key(Key::Enter);
+
+        assert_eq!(logic.word(), "ĈAPELO");
+        assert_eq!(logic.typed_word_length(), None);
+        assert_eq!(logic.used_words.len(), 1);
+
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.word(), "ERARO");
+
+        // Pressing Insert again should cancel
+        logic.press_key(Key::Insert);
+        logic.press_key(Key::Letter('a'));
+        logic.press_key(Key::Insert);
+        assert_eq!(logic.typed_word_length(), None);
+        assert_eq!(logic.word(), "ERARO");
+        logic.press_key(Key::Letter('a'));
+        assert_eq!(logic.in_progress_guess(), "EA");
+    }
+
+    #[test]
+    fn input_method() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        logic.set_word("sano");
+
+        // The first letter can’t be given a hat
+        for ch in "sxaux".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        assert_eq!(logic.in_progress_guess(), "SAŬ");
+
+        // Backspace straight after a conversion undoes it
+        logic.press_key(Key::Backspace);
+        assert_eq!(logic.in_progress_guess(), "SAU");
+        logic.press_key(Key::Backspace);
+        assert_eq!(logic.in_progress_guess(), "SA");
+
+        logic.set_input_method(InputMethod::HSystem);
+
+        for ch in "sh".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        assert_eq!(logic.in_progress_guess(), "SAŜ");
+        logic.press_key(Key::Backspace);
+        assert_eq!(logic.in_progress_guess(), "SASH");
+
+        // Other keys forget the conversion
+        logic.press_key(Key::Backspace);
+        logic.press_key(Key::Letter('h'));
+        logic.press_key(Key::Left);
+        logic.press_key(Key::Right);
+        logic.press_key(Key::Backspace);
+        assert_eq!(logic.in_progress_guess(), "SA");
+
+        // The word typed by the host uses the same input method
+        logic.press_key(Key::Insert);
+        for ch in "chapelo".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        assert_eq!(logic.typed_word.as_deref(), Some("ĈAPELO"));
+        logic.press_key(Key::Insert);
+
+        logic.set_input_method(InputMethod::Direct);
+        logic.press_key(Key::Backspace);
+        logic.press_key(Key::Letter('ĝ'));
+        logic.press_key(Key::Letter('x'));
+        assert_eq!(logic.in_progress_guess(), "SĜ");
+    }
+
+    #[test]
+    fn super_diveno_time_up() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules { super_diveno_time: 0, ..Default::default() },
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        logic.update_timers();
+        assert_eq!(logic.timer_timeout(), Timeout::Forever);
+
+        logic.press_key(Key::Space);
+        logic.press_key(Key::Dollar);
+        logic.super_diveno.as_mut().unwrap().guessed_words = 3;
+
+        while logic.get_event().is_some() {}
+
+        logic.update_timers();
+
+        let mut events = Vec::new();
+        while let Some(event) = logic.get_event() {
+            events.push(event);
+        }
+        assert!(events.contains(&Event::SuperDivenoTimeUp));
+        assert!(events.contains(&Event::ScoreChanged(Team(1))));
+
+        assert_eq!(logic.super_diveno().unwrap().points(), Some(30));
+        assert_eq!(logic.team_score(Team(1)), 30);
+
+        // The event should only be sent once
+        logic.update_timers();
+        assert!(logic.get_event().is_none());
+        assert_eq!(logic.team_score(Team(1)), 30);
+
+        // Guesses can’t be entered anymore
+        for ch in "eraro".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        logic.press_key(Key::Enter);
+        logic.press_key(Key::Delete);
+        assert_eq!(logic.n_guesses(), 0);
+
+        let state = logic.save_state();
+        assert!(state.contains("\nsuper-diveno 1 0 1 3 1 30 1\n"));
+        logic.load_state(&state).unwrap();
+        logic.update_timers();
+        assert_eq!(logic.team_score(Team(1)), 30);
+    }
+
+    #[test]
+    fn super_diveno_team() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules {
+                super_diveno_time: 0,
+                black_ball: BlackBallRule::EndTurn,
+                ..Default::default()
+            },
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        logic.press_key(Key::Dollar);
+        logic.super_diveno.as_mut().unwrap().guessed_words = 3;
+
+        // Ending the turn during the Super Diveno shouldn’t change
+        // which team gets the points
+        logic.black_ball_drawn(Team(0));
+        assert_eq!(logic.current_team(), Team(1));
+
+        // The team should survive saving and loading
+        let state = logic.save_state();
+        assert!(state.contains("\nsuper-diveno 1 0 1 3 0 0 0\n"));
+        logic.load_state(&state).unwrap();
+
+        logic.update_timers();
+        assert_eq!(logic.team_score(Team(0)), 30);
+        assert_eq!(logic.team_score(Team(1)), 0);
+
+        // Older save states fall back to the current team
+        let state = logic.save_state()
+            .replace("diveno-save 7", "diveno-save 6")
+            .replace("\nsuper-diveno 1 0 1 3 1 30 0\n",
+                     "\nsuper-diveno 1 0 1 3 1 30\n");
+        logic.load_state(&state).unwrap();
+        assert_eq!(logic.super_diveno.as_ref().unwrap().team, Team(1));
+    }
+
+    #[test]
+    fn black_ball() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules { n_teams: 3, ..Default::default() },
+            Dictionary::new(Box::new([])),
+            Box::new([]),
+            0,
+        );
+
+        while logic.get_event().is_some() {}
+
+        logic.black_ball_drawn(Team(1));
+        assert!(logic.get_event() == Some(Event::BlackBall(Team(1))));
+        assert!(logic.get_event().is_none());
+
+        logic.rules.black_ball = BlackBallRule::EndTurn;
+        logic.black_ball_drawn(Team(2));
+        assert_eq!(logic.current_team(), Team(0));
+        // Ending the turn starts timing the guess for the new team
+        logic.rules.guess_time = Some(1000);
+        logic.black_ball_drawn(Team(0));
+        assert_eq!(logic.current_team(), Team(1));
+        assert!(logic.guess_remaining_time().is_some());
+
+        logic.rules.black_ball = BlackBallRule::LosePoints(50);
+        logic.scores[1] = 70;
+        logic.black_ball_drawn(Team(1));
+        assert_eq!(logic.team_score(Team(1)), 20);
+        logic.black_ball_drawn(Team(1));
+        assert_eq!(logic.team_score(Team(1)), 0);
+
+        assert_eq!("nothing".parse(), Ok(BlackBallRule::Nothing));
+        assert_eq!("end-turn".parse(), Ok(BlackBallRule::EndTurn));
+        assert_eq!("30".parse(), Ok(BlackBallRule::LosePoints(30)));
+        assert!("lots".parse::<BlackBallRule>().is_err());
+    }
+
+    #[test]
+    fn guess_timer() {
+        let new_logic = |guess_time_up_rejects| {
+            make_logic(Rules {
+                guess_time: Some(0),
+                guess_time_up_rejects,
+                ..Default::default()
+            })
+        };
+
+        let mut logic = new_logic(false);
+
+        assert_eq!(logic.guess_remaining_time(), Some(0));
+        assert_eq!(logic.timer_timeout(), Timeout::Milliseconds(0));
+
+        while logic.get_event().is_some() {}
+
+        logic.update_timers();
+        assert!(logic.get_event() == Some(Event::GuessTimerChanged));
+        assert!(logic.get_event() == Some(Event::GuessTimeUp));
+        assert!(logic.get_event().is_none());
+        assert_eq!(logic.guess_remaining_time(), None);
+        assert_eq!(logic.timer_timeout(), Timeout::Forever);
+        assert_eq!(logic.n_guesses(), 0);
+
+        // Changing the team starts the timer again
+        logic.press_key(Key::Space);
+        assert_eq!(logic.guess_remaining_time(), Some(0));
+
+        let mut logic = new_logic(true);
+
+        logic.press_key(Key::Letter('r'));
+        logic.update_timers();
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.current_team(), Team(1));
+        assert_eq!(logic.guess_remaining_time(), Some(0));
+
+        // A single undo restores the state from before the timeout
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.n_guesses(), 0);
+        assert_eq!(logic.in_progress_guess(), "ER");
+        logic.press_key(Key::Redo);
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.current_team(), Team(1));
+        logic.press_key(Key::Undo);
+
+        // The timer stops once the word is finished
+        for _ in 0..logic.rules.n_guesses {
+            logic.press_key(Key::Delete);
+        }
+        assert_eq!(logic.guess_remaining_time(), None);
+
+        // There’s no timer during the super diveno
+        logic.press_key(Key::Home);
+        assert!(logic.guess_remaining_time().is_some());
+        logic.press_key(Key::Dollar);
+        assert_eq!(logic.guess_remaining_time(), None);
+    }
+
+    #[test]
+    fn steal() {
+        let mut logic = make_logic(Rules { steal: true, ..Default::default() });
+
+        let n_guesses = logic.rules.n_guesses;
+
+        for _ in 0..n_guesses {
+            assert!(!logic.is_stealing);
+            logic.press_key(Key::Delete);
+        }
+
+        // The other team gets an extra guess with an extra hint
+        assert!(logic.is_stealing);
+        assert!(!logic.is_finished());
+        assert_eq!(logic.max_guesses(), n_guesses + 1);
+        assert_eq!(logic.current_team(), Team(1));
+        assert_eq!(logic.visible_letters().count_ones(), 2);
+
+        // The saved state keeps the steal
+        let state = logic.save_state();
+        let mut loaded_logic = make_logic(Rules::default());
+        loaded_logic.load_state(&state).unwrap();
+        assert!(loaded_logic.is_stealing);
+        assert_eq!(loaded_logic.save_state(), state);
+
+        logic.press_key(Key::Delete);
+        assert!(logic.is_finished());
+        assert_eq!(logic.n_guesses(), n_guesses + 1);
+
+        // Only one team can steal the word
+        assert_eq!(logic.current_team(), Team(1));
+
+        logic.press_key(Key::Undo);
+        assert!(logic.is_stealing);
+        logic.press_key(Key::Undo);
+        assert!(!logic.is_stealing);
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.visible_letters().count_ones(), 1);
+
+        // Changing the word stops the steal
+        logic.press_key(Key::Delete);
+        assert!(logic.is_stealing);
+        logic.press_key(Key::Home);
+        assert!(!logic.is_stealing);
+        assert_eq!(logic.max_guesses(), n_guesses);
+    }
+
+    #[test]
+    fn wrong_guess_reason() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        );
+
+        logic.set_word("apple");
+
+        let mut enter_guess = |letters: &str, key: Key| {
+            while logic.get_event().is_some() {}
+
+            for letter in letters.chars() {
+                logic.press_key(Key::Letter(letter));
+            }
+
+            logic.press_key(key);
+
+            let mut reason = None;
+
+            while let Some(event) = logic.get_event() {
+                if let Event::WrongGuessEntered(r) = event {
+                    reason = Some(r);
+                }
+            }
+
+            reason
+        };
+
+        assert!(
+            enter_guess("pp", Key::Enter)
+                == Some(WrongGuessReason::WrongLength)
+        );
+        assert!(
+            enter_guess("el", Key::Enter)
+                == Some(WrongGuessReason::NotInDictionary)
+        );
+        assert!(enter_guess("", Key::Delete).is_none());
+        assert!(enter_guess("pple", Key::Delete).is_none());
+        assert!(
+            enter_guess("pple", Key::Enter)
+                == Some(WrongGuessReason::AlreadyTried(1))
+        );
+    }
+
+    #[test]
+    fn force_enter() {
+        let new_logic = || make_logic(Rules::default());
+
+        let mut logic = new_logic();
+
+        logic.set_word("apple");
+
+        for letter in "ppel".chars() {
+            logic.press_key(Key::Letter(letter));
+        }
+
+        logic.press_key(Key::Enter);
+        assert_eq!(logic.n_guesses(), 0);
+
+        logic.press_key(Key::ForceEnter);
+        assert_eq!(logic.n_guesses(), 1);
+        assert_eq!(logic.accepted_words(), ["APPEL"]);
+
+        // Undoing the guess doesn’t forget that the word was accepted
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.n_guesses(), 0);
+        logic.press_key(Key::ForceEnter);
+        assert_eq!(logic.accepted_words(), ["APPEL"]);
+
+        // Words in the dictionary aren’t added to the list
+        for letter in "pple".chars() {
+            logic.press_key(Key::Letter(letter));
+        }
+        logic.press_key(Key::ForceEnter);
+        assert!(logic.is_solved());
+        assert_eq!(logic.accepted_words(), ["APPEL"]);
+
+        let state = logic.save_state();
+        let mut loaded_logic = new_logic();
+        loaded_logic.load_state(&state).unwrap();
+        assert_eq!(loaded_logic.accepted_words(), ["APPEL"]);
+
+        let bad_state = state.replace("accepted-words APPEL", "accepted-words A1");
+        assert_eq!(
+            loaded_logic.load_state(&bad_state),
+            Err("line 17: invalid accepted word".to_string()),
+        );
+    }
+
+    #[test]
+    fn possible_words() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        );
+
+        logic.set_word("apple");
+        assert_eq!(logic.possible_words(), ["APPLE"]);
+
+        logic.set_word("b");
+        // Hide the first letter so that there are more candidates
+        logic.visible_letters = 0;
+        assert_eq!(logic.possible_words(), ["A", "B", "C"]);
+
+        logic.guesses[0] = vec![
+            Letter { letter: 'A', result: LetterResult::Wrong },
+        ];
+        logic.guesses[1] = vec![
+            Letter { letter: 'C', result: LetterResult::Rejected },
+        ];
+        logic.n_guesses = 2;
+        assert_eq!(logic.possible_words(), ["B", "C"]);
+
+        logic.guesses[1][0].result = LetterResult::Wrong;
+        assert_eq!(logic.possible_words(), ["B"]);
+
+        // A guess with a letter in the wrong position
+        logic.set_word("ĉapelo");
+        logic.visible_letters = 0;
+        logic.guesses[0] = "ĈAPOLE".chars().map(|letter| {
+            let result = match letter {
+                'O' | 'E' => LetterResult::WrongPosition,
+                _ => LetterResult::Correct,
+            };
+            Letter { letter, result }
+        }).collect();
+        logic.n_guesses = 1;
+        assert_eq!(logic.possible_words(), ["ĈAPELO"]);
+
+        logic.guesses[0][3].result = LetterResult::Wrong;
+        assert!(logic.possible_words().is_empty());
+    }
+
+    #[test]
+    fn auto_pass() {
+        let mut logic = make_logic(Rules {
+            auto_pass: true,
+            hint_on_pass: true,
+            ..Default::default()
+        });
+
+        logic.set_word("apple");
+
+        while logic.get_event().is_some() {}
+
+        // A word that isn’t in the dictionary doesn’t pass the turn
+        for letter in "ppel".chars() {
+            logic.press_key(Key::Letter(letter));
+        }
+        logic.press_key(Key::Enter);
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.n_guesses(), 0);
+
+        // Rejecting the guess passes the turn and gives a hint
+        logic.press_key(Key::Delete);
+        assert_eq!(logic.current_team(), Team(1));
+        assert_eq!(logic.visible_letters().count_ones(), 2);
+
+        let mut team_changed = false;
+
+        while let Some(event) = logic.get_event() {
+            if event == Event::CurrentTeamChanged {
+                team_changed = true;
+            }
+        }
+
+        assert!(team_changed);
+
+        // The hint and the team change are undone with the guess
+        logic.press_key(Key::Undo);
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.n_guesses(), 0);
+        assert_eq!(logic.visible_letters().count_ones(), 1);
+        assert_eq!(logic.in_progress_guess(), "APPEL");
+
+        // Solving the word doesn’t pass the turn
+        for _ in 0..5 {
+            logic.press_key(Key::Backspace);
+        }
+        for letter in "pple".chars() {
+            logic.press_key(Key::Letter(letter));
+        }
+        logic.press_key(Key::Enter);
+        assert!(logic.is_solved());
+        assert_eq!(logic.current_team(), Team(0));
+        assert_eq!(logic.team_score(Team(0)), logic.rules.word_points);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let new_logic = |n_teams| {
+            Logic::new(
+                Rc::new(Language::default()),
+                Rules { n_teams, ..Default::default() },
+                Dictionary::new(Box::new([])),
+                Box::new([]),
+                1,
+            )
+        };
+
+        let mut logic = new_logic(3);
+
+        for ch in "er".chars() {
+            logic.press_key(Key::Letter(ch));
+        }
+        logic.press_key(Key::Delete);
+        logic.press_key(Key::PageDown);
+        logic.press_key(Key::Letter('a'));
+        logic.press_key(Key::Space);
+        logic.press_key(Key::Up);
+        logic.press_key(Key::Left);
+        logic.press_key(Key::Left);
+        logic.press_key(Key::Up);
+        logic.press_key(Key::Up);
+
+        let state = logic.save_state();
+
+        let mut loaded_logic = new_logic(2);
+        loaded_logic.load_state(&state).unwrap();
+
+        assert_eq!(loaded_logic.n_teams(), 3);
+        assert_eq!(loaded_logic.current_team(), Team(1));
+        assert_eq!(loaded_logic.team_score(Team(0)), 20);
+        assert_eq!(loaded_logic.team_score(Team(1)), 10);
+        assert_eq!(loaded_logic.in_progress_guess(), "EA");
+        assert_eq!(loaded_logic.n_guesses(), 1);
+        assert_eq!(loaded_logic.visible_letters(), logic.visible_letters());
+
+        let guess = loaded_logic.guesses().next().unwrap();
+        assert_eq!(
+            guess.iter().map(|l| l.letter).collect::<String>(),
+            "ER   ",
+        );
+        assert!(guess.iter().all(|l| l.result == LetterResult::Rejected));
+
+        assert_eq!(loaded_logic.save_state(), state);
+
+        logic.used_words.extend(["c", "app"].map(str::to_string));
+        let state = logic.save_state();
+        assert!(state.contains("\nused-words app,c\n"));
+        loaded_logic.load_state(&state).unwrap();
+        assert_eq!(loaded_logic.used_words, logic.used_words);
+
+        let bad_state = state.replace("scores 20,10,0", "scores 20,10");
+        assert_eq!(
+            loaded_logic.load_state(&bad_state),
+            Err("line 4: wrong number of scores".to_string()),
+        );
+        assert_eq!(loaded_logic.save_state(), state);
+    }
+
+    #[test]
+    fn load_legacy_used_words() {
+        let mut logic = Logic::new(
+            Rc::new(Language::default()),
+            Rules::default(),
+            Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES)),
+            Box::new([]),
+            0,
+        );
+
+        // Before version 6 the used words were saved with the
+        // encoding of the first version of the word list format
+        let state = logic.save_state()
+            .replace("diveno-save 7", "diveno-save 5")
+            .replace("\nused-words\n", "\nused-words 32,2,4\n");
+
+        logic.load_state(&state).unwrap();
 
-    pub fn complete(self) -> Logic {
-        Logic::new(self.dictionary.unwrap(), self.word_list.unwrap())
+        let mut used_words = logic.used_words.iter().collect::<Vec<_>>();
+        used_words.sort_unstable();
+        assert_eq!(used_words, ["app", "c"]);
     }
 }
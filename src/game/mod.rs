@@ -21,13 +21,19 @@ pub mod game_painter;
 pub mod logic;
 pub mod buffer;
 pub mod letter_texture;
+pub mod language;
 pub mod array_object;
 pub mod quad_tool;
 pub mod timer;
 pub mod dictionary;
+pub mod word_list;
+pub mod morphology;
+pub mod input_method;
+pub mod difficulty;
 pub mod random;
 pub mod timing;
 pub mod sound_queue;
 pub mod tombola;
 pub mod bingo_grid;
 pub mod timeout;
+pub mod save_state;
@@ -0,0 +1,157 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Esperanto words are built regularly from a root, affixes and a
+// grammatical ending. The dictionary only contains some of the
+// possible forms so this module strips the endings and affixes from
+// a word and checks whether the root is used by any word in the
+// dictionary. That way forms like “hundetojn” can be accepted as long
+// as the dictionary has a word like “hundo”.
+
+use super::dictionary::Dictionary;
+
+// Grammatical endings that can be removed to get the root. The
+// nominal endings can be followed by the plural -j and the accusative
+// -n.
+static ENDINGS: [&str; 16] = [
+    "o", "oj", "on", "ojn",
+    "a", "aj", "an", "ajn",
+    "e", "en",
+    "i", "as", "is", "os", "us", "u",
+];
+
+// Endings to try adding to a root to see if any word in the
+// dictionary uses it
+static ROOT_ENDINGS: [&str; 11] = [
+    "o", "a", "e", "i", "as", "is", "os", "us", "u", "oj", "aj",
+];
+
+static PREFIXES: [&str; 9] = [
+    "bo", "dis", "ek", "eks", "fi", "ge", "mal", "pra", "re",
+];
+
+static SUFFIXES: [&str; 35] = [
+    "aĉ", "ad", "aĵ", "an", "ant", "ar", "at", "ebl", "ec", "eg", "ej",
+    "em", "end", "er", "estr", "et", "id", "ig", "iĝ", "il", "in",
+    "ind", "ing", "int", "ism", "ist", "it", "obl", "on", "ont", "op",
+    "ot", "uj", "ul", "um",
+];
+
+// The maximum number of affixes that will be removed from a word
+const MAX_AFFIXES: u32 = 3;
+
+// The shortest root that will be considered after removing affixes
+const MIN_ROOT_LENGTH: usize = 2;
+
+/// Returns whether the word is in the dictionary or is a regular
+/// inflected or derived form of a root used by a word in the
+/// dictionary.
+pub fn contains(dictionary: &Dictionary, word: &str) -> bool {
+    dictionary.contains(word)
+        || is_derived_word(word, |form| dictionary.contains(form))
+}
+
+fn is_derived_word<F>(word: &str, lookup: F) -> bool
+where
+    F: Fn(&str) -> bool
+{
+    let word = word.to_lowercase();
+
+    ENDINGS.iter().any(|ending| {
+        word.strip_suffix(ending).map(|root| {
+            is_known_root(root, &lookup, MAX_AFFIXES)
+        }).unwrap_or(false)
+    })
+}
+
+fn is_known_root<F>(root: &str, lookup: &F, max_affixes: u32) -> bool
+where
+    F: Fn(&str) -> bool
+{
+    if root.chars().count() < MIN_ROOT_LENGTH {
+        return false;
+    }
+
+    let mut form = String::with_capacity(root.len() + 2);
+
+    for ending in ROOT_ENDINGS.iter() {
+        form.clear();
+        form.push_str(root);
+        form.push_str(ending);
+
+        if lookup(&form) {
+            return true;
+        }
+    }
+
+    if max_affixes == 0 {
+        return false;
+    }
+
+    SUFFIXES.iter()
+        .filter_map(|suffix| root.strip_suffix(suffix))
+        .chain(PREFIXES.iter().filter_map(|prefix| root.strip_prefix(prefix)))
+        .any(|root| is_known_root(root, lookup, max_affixes - 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn derived_words() {
+        let dictionary = ["hundo", "bela", "kuras", "sana", "ĝardeno"]
+            .into_iter()
+            .collect::<HashSet<&str>>();
+        let lookup = |word: &str| dictionary.contains(word);
+
+        for word in [
+            "hundoj",
+            "hundojn",
+            "hundon",
+            "belaj",
+            "belajn",
+            "bele",
+            "kuri",
+            "kuris",
+            "kurus",
+            "kuru",
+            "hundetojn",
+            "malbelan",
+            "resanigis",
+            "ĝardenisto",
+            "ĜARDENISTOJ",
+        ] {
+            assert!(is_derived_word(word, lookup), "{}", word);
+        }
+
+        for word in [
+            "",
+            "o",
+            "hund",
+            "hundojnj",
+            "katoj",
+            "hundx",
+            // Too many affixes
+            "malremalhundetoj",
+            // The root is too short after removing the affixes
+            "eto",
+        ] {
+            assert!(!is_derived_word(word, lookup), "{}", word);
+        }
+    }
+}
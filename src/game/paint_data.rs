@@ -17,11 +17,14 @@
 use std::rc::Rc;
 use std::cell::Cell;
 use super::{shaders, images, quad_tool};
+use super::language::Language;
 
 pub struct PaintData {
     pub gl: Rc<glow::Context>,
     pub shaders: shaders::Shaders,
     pub images: images::ImageSet,
+    // Used to find the letters in the tile atlas
+    pub language: Rc<Language>,
     pub quad_tool: quad_tool::QuadTool,
     pub has_vertex_array_object: bool,
     pub enabled_attribs: Cell<u32>,
@@ -33,6 +36,7 @@ impl PaintData {
         has_vertex_array_object: bool,
         shaders: shaders::Shaders,
         images: images::ImageSet,
+        language: Rc<Language>,
     ) -> PaintData {
         let quad_tool = quad_tool::QuadTool::new(Rc::clone(&gl));
 
@@ -41,6 +45,7 @@ impl PaintData {
             has_vertex_array_object,
             shaders,
             images,
+            language,
             quad_tool,
             enabled_attribs: Cell::new(0),
         }
@@ -15,27 +15,96 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 #[cfg(not(target_arch = "wasm32"))]
-use rand::Rng;
+use rand::Rng as _;
 
-pub fn random_range(max: usize) -> usize {
+/// Returns a seed picked from the system’s random number source
+pub fn random_seed() -> u64 {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(0..max)
+        rand::thread_rng().gen()
     }
 
     #[cfg(target_arch = "wasm32")]
     {
-        (js_sys::Math::random() * max as f64).floor() as usize
+        // Math.random only gives us 52 bits so combine two numbers
+        let high = (js_sys::Math::random() * (1u64 << 32) as f64) as u64;
+        let low = (js_sys::Math::random() * (1u64 << 32) as f64) as u64;
+        (high << 32) | low
     }
 }
 
-pub fn shuffle<T>(slice: &mut [T]) {
-    for i in (1..slice.len()).rev() {
-        let j = random_range(i + 1);
-        if i != j {
-            let (a, b) = slice.split_at_mut(i);
-            std::mem::swap(&mut a[j], &mut b[0]);
+/// A small pseudo-random number generator that always generates the
+/// same sequence of numbers for a given seed. This uses the SplitMix64
+/// algorithm.
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // Returns a number in the range [0, max)
+    pub fn range(&mut self, max: usize) -> usize {
+        assert!(max > 0);
+
+        // Multiply by the range and take the top bits. The bias this
+        // introduces is negligible for the small ranges that we use.
+        ((self.next_u64() as u128 * max as u128) >> 64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.range(i + 1);
+            if i != j {
+                let (a, b) = slice.split_at_mut(i);
+                std::mem::swap(&mut a[j], &mut b[0]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
         }
+
+        // Check against the reference SplitMix64 output for seed 0
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xe220a8397b1dcdaf);
+        assert_eq!(rng.next_u64(), 0x6e789e6aa1b965f4);
+    }
+
+    #[test]
+    fn range() {
+        let mut rng = Rng::new(7);
+        let mut seen = 0u32;
+
+        for _ in 0..1000 {
+            let value = rng.range(10);
+            assert!(value < 10);
+            seen |= 1 << value;
+        }
+
+        assert_eq!(seen, (1 << 10) - 1);
     }
 }
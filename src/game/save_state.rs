@@ -0,0 +1,273 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Helpers for the text format used to save a game so that it can be
+// resumed later. The first line identifies the file and its version.
+// Every other line starts with a keyword followed by values separated
+// by spaces. Lists of values are separated by commas.
+
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+const HEADER: &str = "diveno-save";
+// This should be incremented whenever the format changes. Files with
+// an older version can still be read.
+pub const VERSION: u32 = 7;
+
+pub struct Writer {
+    buf: String,
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer {
+            buf: format!("{} {}\n", HEADER, VERSION),
+        }
+    }
+
+    pub fn line(&mut self, key: &str) -> LineWriter<'_> {
+        self.buf.push_str(key);
+
+        LineWriter {
+            buf: &mut self.buf,
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+pub struct LineWriter<'a> {
+    buf: &'a mut String,
+}
+
+impl<'a> LineWriter<'a> {
+    pub fn add(&mut self, value: impl Display) -> &mut LineWriter<'a> {
+        let _ = write!(self.buf, " {}", value);
+        self
+    }
+
+    pub fn add_list<I>(&mut self, values: I) -> &mut LineWriter<'a>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        for (index, value) in values.into_iter().enumerate() {
+            self.buf.push(if index > 0 { ',' } else { ' ' });
+
+            let _ = write!(self.buf, "{}", value);
+        }
+
+        self
+    }
+}
+
+impl<'a> Drop for LineWriter<'a> {
+    fn drop(&mut self) {
+        self.buf.push('\n');
+    }
+}
+
+pub struct Reader<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    version: u32,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(source: &'a str) -> Result<Reader<'a>, String> {
+        let mut reader = Reader {
+            lines: source.lines().enumerate(),
+            version: VERSION,
+        };
+
+        let mut fields = reader.line(HEADER)?;
+        let version = fields.next_number::<u32>()?;
+        fields.finish()?;
+
+        if !(1..=VERSION).contains(&version) {
+            return Err(format!(
+                "unsupported save file version {} (expected at most {})",
+                version,
+                VERSION,
+            ));
+        }
+
+        reader.version = version;
+
+        Ok(reader)
+    }
+
+    // Version of the file being read. This can be used to skip lines
+    // that weren’t in older versions.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn line(&mut self, key: &str) -> Result<Fields<'a>, String> {
+        let Some((line_num, line)) = self.lines.next()
+        else {
+            return Err(format!("save file ended before “{}”", key));
+        };
+
+        let mut parts = line.split_ascii_whitespace();
+
+        if parts.next() != Some(key) {
+            return Err(format!(
+                "line {}: expected “{}”",
+                line_num + 1,
+                key,
+            ));
+        }
+
+        Ok(Fields {
+            line_num: line_num + 1,
+            parts,
+        })
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        match self.lines.find(|(_, line)| !line.trim().is_empty()) {
+            Some((line_num, _)) => Err(format!(
+                "line {}: unexpected data at end of save file",
+                line_num + 1,
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct Fields<'a> {
+    line_num: usize,
+    parts: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line_num, message)
+    }
+
+    pub fn next_optional_str(&mut self) -> Option<&'a str> {
+        self.parts.next()
+    }
+
+    pub fn next_str(&mut self) -> Result<&'a str, String> {
+        self.parts.next().ok_or_else(|| self.error("missing value"))
+    }
+
+    pub fn next_number<T: FromStr>(&mut self) -> Result<T, String> {
+        let value = self.next_str()?;
+
+        value.parse::<T>().map_err(|_| {
+            self.error(&format!("invalid number “{}”", value))
+        })
+    }
+
+    pub fn next_bool(&mut self) -> Result<bool, String> {
+        match self.next_str()? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            value => Err(self.error(&format!("invalid boolean “{}”", value))),
+        }
+    }
+
+    pub fn next_list<T: FromStr>(&mut self) -> Result<Vec<T>, String> {
+        let value = self.next_str()?;
+
+        value.split(',').map(|part| {
+            part.parse::<T>().map_err(|_| {
+                self.error(&format!("invalid number “{}”", part))
+            })
+        }).collect()
+    }
+
+    // Same as next_list except that a missing value is treated as an
+    // empty list
+    pub fn next_optional_list<T: FromStr>(
+        &mut self,
+    ) -> Result<Vec<T>, String> {
+        if self.parts.clone().next().is_none() {
+            Ok(Vec::new())
+        } else {
+            self.next_list()
+        }
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        if self.parts.next().is_some() {
+            Err(self.error("too many values"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut writer = Writer::new();
+        writer.line("numbers").add(3).add(true as u8).add_list([1, 2, 3]);
+        writer.line("empty").add_list(Vec::<u32>::new());
+        let source = writer.finish();
+
+        assert_eq!(source, "diveno-save 7\nnumbers 3 1 1,2,3\nempty\n");
+
+        let mut reader = Reader::new(&source).unwrap();
+        assert_eq!(reader.version(), VERSION);
+
+        let mut fields = reader.line("numbers").unwrap();
+        assert_eq!(fields.next_number::<u32>(), Ok(3));
+        assert_eq!(fields.next_bool(), Ok(true));
+        assert_eq!(fields.next_list::<u8>(), Ok(vec![1, 2, 3]));
+        assert!(fields.finish().is_ok());
+
+        let mut fields = reader.line("empty").unwrap();
+        assert_eq!(fields.next_optional_list::<u32>(), Ok(Vec::new()));
+        assert_eq!(fields.next_optional_str(), None);
+        assert_eq!(
+            fields.next_str(),
+            Err("line 3: missing value".to_string()),
+        );
+
+        assert!(reader.finish().is_ok());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Reader::new("diveno-save 8\n").err().unwrap(),
+            "unsupported save file version 8 (expected at most 7)",
+        );
+        assert_eq!(Reader::new("diveno-save 1\n").unwrap().version(), 1);
+        assert_eq!(
+            Reader::new("something else\n").err().unwrap(),
+            "line 1: expected “diveno-save”",
+        );
+
+        let mut reader = Reader::new("diveno-save 1\nscore x\n").unwrap();
+        assert_eq!(
+            reader.line("score").unwrap().next_number::<u32>(),
+            Err("line 2: invalid number “x”".to_string()),
+        );
+        assert_eq!(
+            reader.line("score").err().unwrap(),
+            "save file ended before “score”",
+        );
+    }
+}
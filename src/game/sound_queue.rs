@@ -25,15 +25,23 @@ pub enum Sound {
     WrongPosition,
     WrongLetter,
     BadWord,
+    WrongLength,
+    AlreadyTried,
     Solved,
+    TimeUp,
+    BlackBall,
 }
 
-pub static SOUND_FILES: [&'static str; 5] = [
+pub static SOUND_FILES: [&'static str; 9] = [
     "correct-letter.wav",
     "wrong-position.wav",
     "wrong-letter.wav",
     "bad-word.wav",
+    "wrong-length.wav",
+    "already-tried.wav",
     "solved.wav",
+    "time-up.wav",
+    "black-ball.wav",
 ];
 
 pub struct SoundQueue {
@@ -109,8 +117,15 @@ impl SoundQueue {
             logic::Event::WordChanged => (),
             logic::Event::GridChanged => (),
             logic::Event::GuessEntered => self.queue_guess_sounds(logic),
-            logic::Event::WrongGuessEntered => {
-                self.queue_sound(Sound::BadWord, 0);
+            logic::Event::WrongGuessEntered(reason) => {
+                let sound = match reason {
+                    logic::WrongGuessReason::WrongLength => Sound::WrongLength,
+                    logic::WrongGuessReason::NotInDictionary => Sound::BadWord,
+                    logic::WrongGuessReason::AlreadyTried(_) => {
+                        Sound::AlreadyTried
+                    },
+                };
+                self.queue_sound(sound, 0);
             },
             logic::Event::GuessRejected => (),
             logic::Event::Solved => self.queue_solved(logic),
@@ -121,8 +136,16 @@ impl SoundQueue {
             logic::Event::BingoReset(_) => (),
             logic::Event::BingoChanged(..) => (),
             logic::Event::Bingo(..) => (),
+            logic::Event::BlackBall(_) => {
+                self.queue_sound(Sound::BlackBall, 0);
+            },
             logic::Event::SuperDivenoToggled => (),
             logic::Event::SuperDivenoPauseToggled => (),
+            logic::Event::SuperDivenoTimeUp | logic::Event::GuessTimeUp => {
+                self.queue_sound(Sound::TimeUp, 0);
+            },
+            logic::Event::GuessTimerChanged => (),
+            logic::Event::StealStarted => (),
         }
     }
 
@@ -107,6 +107,8 @@ pub struct Tombola {
     claw_y: f32,
 
     chosen_ball: Option<usize>,
+    // Bitmask of balls that have been taken out of the tombola
+    drawn_balls: u64,
 
     rigid_body_set: RigidBodySet,
     collider_set: ColliderSet,
@@ -126,6 +128,8 @@ pub struct Tombola {
 
 impl Tombola {
     pub fn new(n_balls: usize) -> Tombola {
+        assert!(n_balls <= u64::BITS as usize);
+
         let mut rigid_body_set = RigidBodySet::new();
         let mut collider_set = ColliderSet::new();
         let mut ball_handles = Vec::with_capacity(n_balls);
@@ -191,6 +195,7 @@ impl Tombola {
             claw_y: CLAW_MAX,
 
             chosen_ball: None,
+            drawn_balls: 0,
 
             rigid_body_set,
             collider_set,
@@ -381,6 +386,7 @@ impl Tombola {
             ball_body.set_body_type(RigidBodyType::Dynamic, true);
             self.spin_stage = SpinStage::SlidingIn(self.steps_executed);
             self.chosen_ball = Some(ball);
+            self.drawn_balls |= 1 << ball;
         } else {
             self.claw_x = claw_pos;
 
@@ -542,6 +548,7 @@ impl Tombola {
         self.claw_y = CLAW_MAX;
 
         self.chosen_ball = None;
+        self.drawn_balls = 0;
 
         let packer = HexagonalPacker::new(
             BALL_SIZE / 2.0,
@@ -560,6 +567,31 @@ impl Tombola {
     pub fn take_chosen_ball(&mut self) -> Option<usize> {
         self.chosen_ball.take()
     }
+
+    pub fn drawn_balls(&self) -> u64 {
+        self.drawn_balls
+    }
+
+    // Resets the tombola and then takes out the balls in the bitmask
+    // as if they had been drawn. The balls are dropped in a column
+    // from where the claw releases them so that they will fall onto
+    // the slope.
+    pub fn restore_drawn_balls(&mut self, drawn_balls: u64) {
+        self.reset();
+
+        self.drawn_balls = drawn_balls;
+
+        let x = (TOMBOLA_EXTENT + WALL_X) / 2.0;
+        let mut y = CLAW_MAX;
+
+        for (ball_num, &ball_handle) in self.ball_handles.iter().enumerate() {
+            if drawn_balls & (1 << ball_num) != 0 {
+                let ball_body = &mut self.rigid_body_set[ball_handle];
+                ball_body.set_translation(vector![x, y], true);
+                y += BALL_SIZE * 1.1;
+            }
+        }
+    }
 }
 
 pub struct BallIter<'a> {
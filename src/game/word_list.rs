@@ -0,0 +1,260 @@
+// Diveno – A word game in Esperanto
+// Copyright (C) 2023  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The word list contains the words from the dictionary that can be
+// picked as the word to guess. Each word is stored as the path
+// through the dictionary graph to reach it. For each letter there is
+// a number of siblings to skip before descending to a child. The
+// word is finished when the path reaches a '\0' character.
+//
+// The first version of the format has no header and stores each word
+// in a little-endian u64 split into 5-bit numbers. That limits the
+// words to 12 letters and the path can’t skip more than 31 siblings.
+//
+// The later versions start with MAGIC followed by a byte for the
+// version number. In version 2 each word is stored as a sequence of
+// variable-length numbers in the same way as the offsets in the
+// dictionary, so there is no limit on the length of the words or the
+// number of siblings. The game still can’t use words longer than
+// MAX_WORD_LENGTH so they are rejected.
+
+use super::dictionary::Dictionary;
+
+/// Bytes at the start of a word list in the versioned format. Read
+/// as a u64 it has some of the top four bits set, which can’t happen
+/// for a word in the first version because a 12-letter word only
+/// uses 60 bits.
+pub const MAGIC: [u8; 8] = *b"DIVENOWL";
+
+/// The latest version of the format
+pub const VERSION: u8 = 2;
+
+/// Maximum number of letters in a word that the game can use. The
+/// visible letters of the word are stored as a bitmask in a u32.
+pub const MAX_WORD_LENGTH: usize = u32::BITS as usize;
+
+const LEGACY_WORD_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Builds a word list in the latest version of the format from the
+/// encodings of the words given by the dictionary in
+/// FoundWord::encoding.
+// This is only used by make-word-list in the utils crate
+#[allow(dead_code)]
+pub fn encode<'a, I>(encodings: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut data = MAGIC.to_vec();
+
+    data.push(VERSION);
+
+    for encoding in encodings {
+        data.extend_from_slice(encoding);
+    }
+
+    data
+}
+
+/// Decodes a word list in any version of the format and returns the
+/// words in the same form as the dictionary.
+pub fn decode(
+    dictionary: &Dictionary,
+    data: &[u8],
+) -> Result<Vec<String>, String> {
+    match data.strip_prefix(&MAGIC) {
+        Some(data) => decode_versioned(dictionary, data),
+        None => decode_legacy(dictionary, data),
+    }
+}
+
+fn decode_legacy(
+    dictionary: &Dictionary,
+    data: &[u8],
+) -> Result<Vec<String>, String> {
+    if !data.len().is_multiple_of(LEGACY_WORD_SIZE) {
+        return Err("the word list is truncated".to_string());
+    }
+
+    data.chunks_exact(LEGACY_WORD_SIZE)
+        .enumerate()
+        .map(|(word_num, bytes)| {
+            let word = u64::from_le_bytes(bytes.try_into().unwrap());
+
+            dictionary.extract_legacy_word(word).ok_or_else(|| {
+                format!("invalid word at index {}", word_num)
+            })
+        })
+        .collect()
+}
+
+fn decode_versioned(
+    dictionary: &Dictionary,
+    data: &[u8],
+) -> Result<Vec<String>, String> {
+    let Some((&version, mut data)) = data.split_first()
+    else {
+        return Err("missing version number".to_string());
+    };
+
+    if version != VERSION {
+        return Err(format!("unsupported version: {}", version));
+    }
+
+    let mut words = Vec::new();
+
+    while !data.is_empty() {
+        let Some((word, remainder)) = dictionary.extract_word(data)
+        else {
+            return Err(format!("invalid word at index {}", words.len()));
+        };
+
+        if word.chars().count() > MAX_WORD_LENGTH {
+            return Err(format!("word at index {} is too long", words.len()));
+        }
+
+        words.push(word);
+        data = remainder;
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary;
+
+    fn test_dictionary() -> Dictionary {
+        Dictionary::new(Box::new(dictionary::test::DICTIONARY_BYTES))
+    }
+
+    #[test]
+    fn legacy() {
+        let dictionary = test_dictionary();
+
+        let data = [0u64, 32800, 3].into_iter()
+            .flat_map(u64::to_le_bytes)
+            .collect::<Vec<u8>>();
+
+        assert_eq!(
+            decode(&dictionary, &data),
+            Ok(vec!["a".to_string(), "apple".to_string(), "ĉapelo".to_string()]),
+        );
+
+        assert_eq!(decode(&dictionary, &[]), Ok(Vec::new()));
+        assert_eq!(
+            decode(&dictionary, &data[0..12]),
+            Err("the word list is truncated".to_string()),
+        );
+        assert_eq!(
+            decode(&dictionary, &4u64.to_le_bytes()),
+            Err("invalid word at index 0".to_string()),
+        );
+    }
+
+    #[test]
+    fn versioned() {
+        let dictionary = test_dictionary();
+
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+
+        assert_eq!(decode(&dictionary, &data), Ok(Vec::new()));
+
+        // “app”, “c” and “ĉapelo”
+        data.extend_from_slice(&[0, 1, 0, 0]);
+        data.extend_from_slice(&[2, 0]);
+        data.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(
+            decode(&dictionary, &data),
+            Ok(vec!["app".to_string(), "c".to_string(), "ĉapelo".to_string()]),
+        );
+
+        data.extend_from_slice(&[0, 1]);
+
+        assert_eq!(
+            decode(&dictionary, &data),
+            Err("invalid word at index 3".to_string()),
+        );
+
+        assert_eq!(
+            decode(&dictionary, &MAGIC),
+            Err("missing version number".to_string()),
+        );
+
+        let mut data = MAGIC.to_vec();
+        data.push(3);
+
+        assert_eq!(
+            decode(&dictionary, &data),
+            Err("unsupported version: 3".to_string()),
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let dictionary = test_dictionary();
+        let (words, encodings): (Vec<String>, Vec<Vec<u8>>) =
+            dictionary.words()
+            .map(|found| (found.word, found.encoding))
+            .unzip();
+
+        let data = encode(encodings.iter().map(Vec::as_slice));
+
+        assert_eq!(decode(&dictionary, &data), Ok(words));
+        assert_eq!(decode(&dictionary, &encode([])), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn too_long() {
+        // A dictionary with a word that has one letter too many
+        let mut data = vec![0, 1, b'*'];
+
+        for _ in 0..=MAX_WORD_LENGTH {
+            data.extend_from_slice(&[0, 1, b'a']);
+        }
+
+        data.extend_from_slice(&[0, 0, 0]);
+
+        let dictionary = Dictionary::new(data.into_boxed_slice());
+
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.resize(data.len() + MAX_WORD_LENGTH + 2, 0);
+
+        assert_eq!(
+            decode(&dictionary, &data),
+            Err("word at index 0 is too long".to_string()),
+        );
+    }
+
+    #[test]
+    fn bundled_word_list() {
+        // The word list that comes with the game still uses the
+        // first version of the format
+        let dictionary = Dictionary::new(Box::new(
+            *include_bytes!("../../data/dictionary.bin")
+        ));
+        let words = decode(
+            &dictionary,
+            include_bytes!("../../data/wordlist.bin"),
+        ).unwrap();
+
+        assert_eq!(words.len(), 58776 / LEGACY_WORD_SIZE);
+        assert!(words.iter().all(|word| dictionary.contains(word)));
+    }
+}
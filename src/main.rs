@@ -18,15 +18,18 @@ mod game;
 mod sdl_images;
 
 use game::{logic, shaders, images, game_painter, paint_data, sound_queue};
-use game::{timer, timeout};
+use game::{timer, timeout, dictionary};
+use game::input_method::InputMethod;
+use game::language;
 
 use sdl2;
 use sdl2::event::{Event, WindowEvent};
 use sdl2::mixer::{Channel, Chunk};
-use sdl2::keyboard::Keycode;
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::video::FullscreenType;
 use std::process::ExitCode;
 use std::rc::Rc;
+use std::path::{Path, PathBuf};
 use glow::HasContext;
 use timeout::Timeout;
 
@@ -69,6 +72,10 @@ impl Context {
             Err(e) => return Err(e.to_string()),
         };
 
+        // Letters are received as text so that the keyboard layout,
+        // dead keys and compose sequences are taken into account
+        video_subsystem.text_input().start();
+
         let gl_context = window.gl_create_context()?;
 
         window.gl_make_current(&gl_context)?;
@@ -108,6 +115,177 @@ fn check_extension(context: &Context, name: &str) -> bool {
     extensions.split(' ').find(|&ext| ext == name).is_some()
 }
 
+// Words to print from the dictionary instead of running the game
+enum WordSearch {
+    // Words with the given length, or all words if the length is zero
+    Length(usize),
+    // Words matching a pattern where “?” matches any letter
+    Pattern(String),
+}
+
+struct Options {
+    rules: logic::Rules,
+    // File to resume the game from and to save the game to whenever
+    // it changes
+    save_file: Option<PathBuf>,
+    // Seed for the random number generator so that a game can be
+    // replayed
+    seed: Option<u64>,
+    // Whether to print the seed when the game starts
+    print_seed: bool,
+    // Text file with a list of words to use instead of the default
+    // word list
+    word_list: Option<PathBuf>,
+    // Text file to write the words that the host accepted even though
+    // they weren’t in the dictionary
+    accepted_words_file: Option<PathBuf>,
+    word_search: Option<WordSearch>,
+    input_method: InputMethod,
+    // Code of the language profile to load from the data directory
+    language: String,
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut options = Options {
+            rules: logic::Rules::default(),
+            save_file: None,
+            seed: None,
+            print_seed: false,
+            word_list: None,
+            accepted_words_file: None,
+            word_search: None,
+            input_method: InputMethod::XSystem,
+            language: language::DEFAULT_LANGUAGE.to_string(),
+        };
+
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-t" | "--teams" => {
+                    let n_teams = parse_number_arg(&arg, args.next())?;
+
+                    if !(1..=logic::MAX_TEAMS).contains(&n_teams) {
+                        return Err(format!(
+                            "the number of teams must be from 1 to {}",
+                            logic::MAX_TEAMS,
+                        ));
+                    }
+
+                    options.rules.n_teams = n_teams;
+                },
+                "-s" | "--save" => {
+                    let path = parse_path_arg(&arg, args.next())?;
+                    options.save_file = Some(path);
+                },
+                "-w" | "--word-list" => {
+                    let path = parse_path_arg(&arg, args.next())?;
+                    options.word_list = Some(path);
+                },
+                "-a" | "--accepted-words" => {
+                    let path = parse_path_arg(&arg, args.next())?;
+                    options.accepted_words_file = Some(path);
+                },
+                "-b" | "--black-ball" => {
+                    let Some(value) = args.next()
+                    else {
+                        return Err(format!("missing argument for {}", arg));
+                    };
+
+                    options.rules.black_ball = value.parse()?;
+                },
+                "-g" | "--guess-time" => {
+                    let seconds = parse_number_arg::<u32>(&arg, args.next())?;
+                    options.rules.guess_time = (seconds > 0).then_some(
+                        seconds as i64 * 1000
+                    );
+                },
+                "--pass-on-timeout" => {
+                    options.rules.guess_time_up_rejects = true;
+                },
+                "--steal" => {
+                    options.rules.steal = true;
+                },
+                "--auto-pass" => {
+                    options.rules.auto_pass = true;
+                },
+                "--hint-on-pass" => {
+                    options.rules.hint_on_pass = true;
+                },
+                "-d" | "--difficulty" => {
+                    let Some(value) = args.next()
+                    else {
+                        return Err(format!("missing argument for {}", arg));
+                    };
+
+                    options.rules.word_difficulty = Some(value.parse()?);
+                },
+                "-i" | "--input-method" => {
+                    let Some(value) = args.next()
+                    else {
+                        return Err(format!("missing argument for {}", arg));
+                    };
+
+                    options.input_method = value.parse()?;
+                },
+                "-l" | "--language" => {
+                    let Some(value) = args.next()
+                    else {
+                        return Err(format!("missing argument for {}", arg));
+                    };
+
+                    options.language = value;
+                },
+                "--list-words" => {
+                    let length = parse_number_arg(&arg, args.next())?;
+                    options.word_search = Some(WordSearch::Length(length));
+                },
+                "--search" => {
+                    let Some(pattern) = args.next()
+                    else {
+                        return Err(format!("missing argument for {}", arg));
+                    };
+
+                    options.word_search = Some(WordSearch::Pattern(pattern));
+                },
+                "--seed" => {
+                    options.seed = Some(parse_number_arg(&arg, args.next())?);
+                },
+                "--print-seed" => {
+                    options.print_seed = true;
+                },
+                _ => return Err(format!("unknown option: {}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number_arg<T: std::str::FromStr>(
+    option: &str,
+    value: Option<String>,
+) -> Result<T, String> {
+    let Some(value) = value
+    else {
+        return Err(format!("missing argument for {}", option));
+    };
+
+    value.parse::<T>().map_err(|_| {
+        format!("invalid value for {}: {}", option, value)
+    })
+}
+
+fn parse_path_arg(
+    option: &str,
+    value: Option<String>,
+) -> Result<PathBuf, String> {
+    value.map(PathBuf::from).ok_or_else(|| {
+        format!("missing argument for {}", option)
+    })
+}
+
 struct GameData<'a> {
     context: &'a mut Context,
     logic: logic::Logic,
@@ -115,9 +293,14 @@ struct GameData<'a> {
     sound_files: Vec<Chunk>,
     sound_queue: sound_queue::SoundQueue,
     game_painter: game_painter::GamePainter,
+    save_file: Option<PathBuf>,
     redraw_time: Option<i64>,
     should_quit: bool,
     is_fullscreen: bool,
+    // Set while the input method is composing some text, for example
+    // after a dead key. The keys pressed during that time are meant
+    // for the input method rather than the game.
+    is_composing: bool,
 }
 
 impl<'a> GameData<'a> {
@@ -125,17 +308,41 @@ impl<'a> GameData<'a> {
         context: &'a mut Context,
         shaders: shaders::Shaders,
         images: images::ImageSet,
+        logic_loader: logic::LogicLoader,
+        options: Options,
     ) -> Result<GameData<'a>, String> {
         let paint_data = Rc::new(paint_data::PaintData::new(
             Rc::clone(&context.gl),
             check_extension(context, "GL_OES_vertex_array_object"),
             shaders,
             images,
+            Rc::clone(logic_loader.language().unwrap()),
         ));
 
-        let game_painter = game_painter::GamePainter::new(paint_data)?;
+        let mut logic = logic_loader.complete(options.rules);
 
-        let logic = load_logic()?;
+        logic.set_input_method(options.input_method);
+
+        if options.print_seed {
+            println!("Seed: {}", logic.seed());
+        }
+
+        if let Some(word_list) = options.word_list.as_ref() {
+            load_word_list(&mut logic, word_list)?;
+        }
+
+        if let Some(save_file) = options.save_file.as_ref() {
+            if save_file.exists() {
+                load_save_file(&mut logic, save_file).map_err(|e| {
+                    format!("{}: {}", save_file.display(), e)
+                })?;
+            }
+        }
+
+        let game_painter = game_painter::GamePainter::new(
+            paint_data,
+            logic.n_teams(),
+        )?;
 
         let sound_files = load_sound_files()?;
 
@@ -146,9 +353,11 @@ impl<'a> GameData<'a> {
             sound_files,
             sound_queue: sound_queue::SoundQueue::new(),
             game_painter,
+            save_file: options.save_file,
             redraw_time: Some(0),
             should_quit: false,
             is_fullscreen: false,
+            is_composing: false,
         })
     }
 }
@@ -165,7 +374,48 @@ fn toggle_fullscreen(game_data: &mut GameData) {
     }
 }
 
-fn handle_keycode_down(game_data: &mut GameData, code: Keycode) {
+// Prints the words that could still be the answer to the terminal so
+// that the host can check them without the players seeing
+fn print_possible_words(logic: &logic::Logic) {
+    let words = logic.possible_words();
+
+    println!(
+        "{} possible dictionary words (derived forms aren’t listed): {}",
+        words.len(),
+        words.join(", "),
+    );
+}
+
+// Prints the difficulty of the next words to the terminal so that the
+// host knows which band was chosen
+fn print_word_difficulty(logic: &logic::Logic) {
+    match logic.word_difficulty() {
+        Some(difficulty) => println!("Next words will be {}", difficulty),
+        None => println!("Next words can be of any difficulty"),
+    }
+}
+
+fn handle_keycode_down(game_data: &mut GameData, code: Keycode, keymod: Mod) {
+    if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) {
+        let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+
+        match code {
+            Keycode::Z if shift => game_data.logic.press_key(logic::Key::Redo),
+            Keycode::Z => game_data.logic.press_key(logic::Key::Undo),
+            Keycode::Y => game_data.logic.press_key(logic::Key::Redo),
+            Keycode::Return => {
+                game_data.logic.press_key(logic::Key::ForceEnter)
+            },
+            Keycode::D => {
+                game_data.logic.press_key(logic::Key::NextDifficulty);
+                print_word_difficulty(&game_data.logic);
+            },
+            _ => (),
+        }
+
+        return;
+    }
+
     match code {
         Keycode::Backspace => game_data.logic.press_key(logic::Key::Backspace),
         Keycode::Delete => game_data.logic.press_key(logic::Key::Delete),
@@ -173,6 +423,8 @@ fn handle_keycode_down(game_data: &mut GameData, code: Keycode) {
         Keycode::PageDown => game_data.logic.press_key(logic::Key::PageDown),
         Keycode::Space => game_data.logic.press_key(logic::Key::Space),
         Keycode::Home => game_data.logic.press_key(logic::Key::Home),
+        Keycode::End => game_data.logic.press_key(logic::Key::End),
+        Keycode::Insert => game_data.logic.press_key(logic::Key::Insert),
         Keycode::Left => game_data.logic.press_key(logic::Key::Left),
         Keycode::Right => game_data.logic.press_key(logic::Key::Right),
         Keycode::Up => game_data.logic.press_key(logic::Key::Up),
@@ -180,12 +432,20 @@ fn handle_keycode_down(game_data: &mut GameData, code: Keycode) {
         Keycode::Backquote => game_data.logic.press_key(logic::Key::Backtick),
         Keycode::Dollar => game_data.logic.press_key(logic::Key::Dollar),
         Keycode::F11 => toggle_fullscreen(game_data),
-        code => {
-            if let Some(ch) = char::from_u32(code as u32) {
-                if ch.is_alphabetic() {
-                    game_data.logic.press_key(logic::Key::Letter(ch));
-                }
-            }
+        Keycode::F2 => print_possible_words(&game_data.logic),
+        // Letters and digits are handled with the text input events
+        _ => (),
+    }
+}
+
+fn handle_text_input(game_data: &mut GameData, text: &str) {
+    game_data.is_composing = false;
+
+    for ch in text.chars() {
+        if ch.is_alphabetic() {
+            game_data.logic.press_key(logic::Key::Letter(ch));
+        } else if let Some(digit) = ch.to_digit(10) {
+            game_data.logic.press_key(logic::Key::Digit(digit));
         }
     }
 }
@@ -197,8 +457,18 @@ fn queue_redraw(game_data: &mut GameData) {
 fn handle_event(game_data: &mut GameData, event: Event) {
     match event {
         Event::Quit {..} => game_data.should_quit = true,
-        Event::KeyDown { keycode: Some(code), .. } => {
-            handle_keycode_down(game_data, code);
+        // The keys pressed while the input method is composing some
+        // text are meant for it rather than the game
+        Event::KeyDown { keycode: Some(code), keymod, .. }
+            if !game_data.is_composing =>
+        {
+            handle_keycode_down(game_data, code, keymod);
+        },
+        Event::TextInput { text, .. } => {
+            handle_text_input(game_data, &text);
+        },
+        Event::TextEditing { text, .. } => {
+            game_data.is_composing = !text.is_empty();
         },
         Event::Window { win_event, .. } => {
             match win_event {
@@ -230,13 +500,94 @@ fn flush_sounds(game_data: &mut GameData) {
 }
 
 fn flush_logic_events(game_data: &mut GameData) {
+    let mut had_event = false;
+
     while let Some(event) = game_data.logic.get_event() {
+        had_event = true;
+
         if game_data.game_painter.handle_logic_event(&game_data.logic, &event) {
             queue_redraw(game_data);
         }
 
         game_data.sound_queue.handle_logic_event(&game_data.logic, &event);
     }
+
+    if had_event {
+        save_game(game_data);
+    }
+}
+
+fn save_game(game_data: &GameData) {
+    let Some(save_file) = game_data.save_file.as_ref()
+    else {
+        return;
+    };
+
+    if let Err(e) = write_save_file(save_file, &game_data.logic.save_state()) {
+        eprintln!("{}: {}", save_file.display(), e);
+    }
+}
+
+fn write_save_file(filename: &Path, state: &str) -> std::io::Result<()> {
+    // Write to a temporary file first so that the previous save won’t
+    // be lost if something goes wrong
+    let mut temp_filename = filename.as_os_str().to_owned();
+    temp_filename.push(".tmp");
+
+    std::fs::write(&temp_filename, state)?;
+    std::fs::rename(&temp_filename, filename)
+}
+
+// Writes the words in the same format as the --word-list option so
+// that they can be used to extend the dictionary
+fn write_accepted_words(
+    filename: &Path,
+    words: &[String],
+) -> std::io::Result<()> {
+    let mut contents = String::new();
+
+    for word in words {
+        contents.push_str(&word.to_lowercase());
+        contents.push('\n');
+    }
+
+    std::fs::write(filename, contents)
+}
+
+fn load_word_list(
+    logic: &mut logic::Logic,
+    filename: &Path,
+) -> Result<(), String> {
+    let source = std::fs::read_to_string(filename).map_err(|e| {
+        format!("{}: {}", filename.display(), e)
+    })?;
+
+    let report = logic.load_word_list_text(&source);
+
+    for rejected in report.rejected.iter() {
+        eprintln!(
+            "{}:{}: {}: {}",
+            filename.display(),
+            rejected.line_num,
+            rejected.word,
+            rejected.reason,
+        );
+    }
+
+    if report.n_words == 0 {
+        Err(format!("{}: no usable words", filename.display()))
+    } else {
+        Ok(())
+    }
+}
+
+fn load_save_file(
+    logic: &mut logic::Logic,
+    filename: &Path,
+) -> Result<(), String> {
+    let state = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+
+    logic.load_state(&state)
 }
 
 fn redraw(game_data: &mut GameData) {
@@ -265,8 +616,9 @@ fn main_loop(game_data: &mut GameData) {
     while !game_data.should_quit {
         let redraw_delay = redraw_delay(game_data);
         let sound_delay = game_data.sound_queue.next_delay();
+        let timer_delay = game_data.logic.timer_timeout();
 
-        match redraw_delay.min(sound_delay) {
+        match redraw_delay.min(sound_delay).min(timer_delay) {
             Timeout::Forever => {
                 let event = game_data.context.event_pump.wait_event();
                 handle_event(game_data, event);
@@ -291,6 +643,8 @@ fn main_loop(game_data: &mut GameData) {
             },
         }
 
+        game_data.logic.update_timers();
+
         flush_logic_events(game_data);
 
         if let Timeout::Milliseconds(ms) = redraw_delay {
@@ -313,14 +667,78 @@ fn load_data_file(filename: &str) -> Result<Vec<u8>, String> {
     std::fs::read(&path).map_err(|e| format!("{}: {}", filename, e))
 }
 
-fn load_logic() -> Result<logic::Logic, String> {
+fn load_language(code: &str) -> Result<language::Language, String> {
+    let filename = language::Language::filename(code);
+    let data = load_data_file(&filename)?;
+
+    std::str::from_utf8(&data)
+        .map_err(|e| e.to_string())
+        .and_then(language::Language::parse)
+        .map_err(|e| format!("{}: {}", filename, e))
+}
+
+fn load_letters_texture(
+    gl: &Rc<glow::Context>,
+    language: &language::Language,
+) -> Result<images::Texture, String> {
+    let font = load_data_file(language.font())?;
+
+    images::create_letters_texture(gl, language, &font)
+        .map_err(|e| format!("{}: {}", language.font(), e))
+}
+
+fn print_words(
+    language: &str,
+    word_search: &WordSearch,
+) -> Result<(), String> {
+    let language = load_language(language)?;
+    let data = load_data_file(language.dictionary())?;
+    let dictionary = dictionary::Dictionary::new(data.into_boxed_slice());
+
+    match word_search {
+        WordSearch::Length(0) => {
+            for found in dictionary.words() {
+                println!("{}", found.word);
+            }
+        },
+        WordSearch::Length(length) => {
+            for found in dictionary.words_of_length(*length) {
+                println!("{}", found.word);
+            }
+        },
+        WordSearch::Pattern(pattern) => {
+            // Also show the word list representation to help with
+            // debugging the word list
+            for found in dictionary.words_matching(pattern) {
+                let encoding = found.encoding.iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>();
+
+                println!("{} {}", found.word, encoding);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn load_logic_data(
+    language: &str,
+    seed: Option<u64>,
+) -> Result<logic::LogicLoader, String> {
     let mut loader = logic::LogicLoader::new();
 
+    loader.set_language(language);
+
+    if let Some(seed) = seed {
+        loader.set_seed(seed);
+    }
+
     while let Some(filename) = loader.next_filename() {
-        loader.loaded(load_data_file(filename)?.into_boxed_slice());
+        loader.loaded(load_data_file(&filename)?.into_boxed_slice())?;
     }
 
-    Ok(loader.complete())
+    Ok(loader)
 }
 
 fn load_shaders(gl: Rc<glow::Context>) -> Result<shaders::Shaders, String> {
@@ -344,6 +762,24 @@ fn load_sound_files() -> Result<Vec<Chunk>, String> {
 }
 
 pub fn main() -> ExitCode {
+    let options = match Options::parse() {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    if let Some(word_search) = options.word_search.as_ref() {
+        return match print_words(&options.language, word_search) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            },
+        };
+    }
+
     let mut context = match Context::new() {
         Ok(c) => c,
         Err(e) => {
@@ -360,7 +796,29 @@ pub fn main() -> ExitCode {
         }
     };
 
-    let images = match sdl_images::load_image_set(&context.gl) {
+    let logic_loader = match load_logic_data(
+        &options.language,
+        options.seed,
+    ) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let letters = match load_letters_texture(
+        &context.gl,
+        logic_loader.language().unwrap(),
+    ) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let images = match sdl_images::load_image_set(&context.gl, letters) {
         Ok(s) => s,
         Err(e) => {
             eprintln!("{}", e);
@@ -368,7 +826,15 @@ pub fn main() -> ExitCode {
         }
     };
 
-    let mut game_data = match GameData::new(&mut context, shaders, images) {
+    let accepted_words_file = options.accepted_words_file.clone();
+
+    let mut game_data = match GameData::new(
+        &mut context,
+        shaders,
+        images,
+        logic_loader,
+        options,
+    ) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("{}", e);
@@ -378,5 +844,18 @@ pub fn main() -> ExitCode {
 
     main_loop(&mut game_data);
 
+    // Save again in case the Super Diveno timer has changed
+    save_game(&game_data);
+
+    if let Some(filename) = accepted_words_file {
+        if let Err(e) = write_accepted_words(
+            &filename,
+            game_data.logic.accepted_words(),
+        ) {
+            eprintln!("{}: {}", filename.display(), e);
+            return ExitCode::FAILURE;
+        }
+    }
+
     ExitCode::SUCCESS
 }
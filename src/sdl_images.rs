@@ -20,7 +20,7 @@ use sdl2::image::LoadSurface;
 use sdl2::surface::Surface;
 use sdl2::pixels::PixelFormatEnum;
 use glow::HasContext;
-use crate::game::images::{ImageSet, ImageLoader};
+use crate::game::images::{ImageSet, ImageLoader, Texture};
 
 fn copy_surface_to_texture(
     gl: &glow::Context,
@@ -111,12 +111,15 @@ fn load_mipmap_texture(
     Ok(id)
 }
 
-pub fn load_image_set(gl: &Rc<glow::Context>) -> Result<ImageSet, String> {
+pub fn load_image_set(
+    gl: &Rc<glow::Context>,
+    letters: Texture,
+) -> Result<ImageSet, String> {
     let mut loader = ImageLoader::new(Rc::clone(gl));
 
     while let Some(filename) = loader.next_filename() {
         loader.loaded(load_mipmap_texture(&gl, filename)?);
     }
 
-    Ok(loader.complete())
+    Ok(loader.complete(letters))
 }
@@ -19,8 +19,9 @@ use web_sys::console;
 use std::rc::Rc;
 use glow::HasContext;
 use super::game;
-use game::images::ImageLoader;
-use game::logic::{LogicLoader, Logic};
+use game::images::{self, ImageLoader, Texture};
+use game::logic::{LogicLoader, Logic, Rules};
+use game::input_method::InputMethod;
 use game::shaders::ShaderLoader;
 use game::paint_data::PaintData;
 use game::game_painter::GamePainter;
@@ -29,6 +30,12 @@ use game::timer::Timer;
 use game::timeout;
 use timeout::Timeout;
 
+// Key used to store the game state in the browser’s local storage
+const SAVE_STORAGE_KEY: &str = "diveno-save";
+// Key used to store the words that the host accepted even though they
+// weren’t in the dictionary so that they can be exported
+const ACCEPTED_WORDS_STORAGE_KEY: &str = "diveno-accepted-words";
+
 fn show_error(message: &str) {
     console::log_1(&message.into());
 
@@ -111,12 +118,23 @@ struct Loader {
     context: Context,
 
     image_loader: ImageLoader,
+    // The letters are drawn with the font from the language profile
+    // once it is loaded
+    letters: Option<Texture>,
     image_load_closure: Option<Closure::<dyn Fn()>>,
     image_error_closure: Option<Closure::<dyn Fn()>>,
 
     logic_loader: LogicLoader,
     shader_loader: ShaderLoader,
 
+    rules: Rules,
+    input_method: InputMethod,
+    // Contents of a text file chosen by the user to replace the
+    // word list
+    word_list: Option<String>,
+    // State to resume the game from
+    saved_state: Option<String>,
+
     data_response_closure: Option<PromiseClosure>,
     data_content_closure: Option<PromiseClosure>,
     data_error_closure: Option<PromiseClosure>,
@@ -125,16 +143,36 @@ struct Loader {
 }
 
 impl Loader {
-    fn new(context: Context) -> Loader {
+    fn new(
+        context: Context,
+        rules: Rules,
+        input_method: InputMethod,
+        language: &str,
+        seed: Option<u64>,
+        word_list: Option<String>,
+        saved_state: Option<String>,
+    ) -> Loader {
+        let mut logic_loader = LogicLoader::new();
+
+        logic_loader.set_language(language);
+
+        if let Some(seed) = seed {
+            logic_loader.set_seed(seed);
+        }
+
         let image_loader = ImageLoader::new(Rc::clone(&context.gl));
-        let logic_loader = LogicLoader::new();
         let shader_loader = ShaderLoader::new(Rc::clone(&context.gl));
 
         Loader {
             context,
             image_loader,
+            letters: None,
             logic_loader,
             shader_loader,
+            rules,
+            input_method,
+            word_list,
+            saved_state,
             image_load_closure: None,
             image_error_closure: None,
             data_response_closure: None,
@@ -263,9 +301,14 @@ impl Loader {
         }
     }
 
-    fn next_data_filename(&self) -> Option<&'static str> {
+    fn next_data_filename(&self) -> Option<String> {
         self.logic_loader.next_filename()
-            .or_else(|| self.shader_loader.next_filename())
+            .or_else(|| {
+                self.letters.is_none().then(|| {
+                    self.logic_loader.language().unwrap().font().to_string()
+                })
+            })
+            .or_else(|| self.shader_loader.next_filename().map(str::to_string))
     }
 
     fn queue_data_load(&mut self) {
@@ -325,9 +368,19 @@ impl Loader {
     }
 
     fn data_loaded(&mut self, data: Vec<u8>) {
-        if self.logic_loader.next_filename().is_some() {
-            self.logic_loader.loaded(data.into_boxed_slice());
-        } else if let Err(e) = self.shader_loader.loaded(&data) {
+        let result = if self.logic_loader.next_filename().is_some() {
+            self.logic_loader.loaded(data.into_boxed_slice())
+        } else if self.letters.is_none() {
+            let language = self.logic_loader.language().unwrap();
+
+            images::create_letters_texture(&self.context.gl, language, &data)
+                .map(|texture| self.letters = Some(texture))
+                .map_err(|e| format!("{}: {}", language.font(), e))
+        } else {
+            self.shader_loader.loaded(&data)
+        };
+
+        if let Err(e) = result {
             show_error(&e);
             self.stop_floating();
             return;
@@ -347,10 +400,20 @@ impl Loader {
             return;
         }
 
-        let Loader { context, image_loader, logic_loader, shader_loader, .. } =
-            self.stop_floating();
-
-        let images = image_loader.complete();
+        let Loader {
+            context,
+            image_loader,
+            letters,
+            logic_loader,
+            shader_loader,
+            rules,
+            input_method,
+            word_list,
+            saved_state,
+            ..
+        } = self.stop_floating();
+
+        let images = image_loader.complete(letters.unwrap());
         let shaders = match shader_loader.complete() {
             Ok(s) => s,
             Err(e) => {
@@ -377,11 +440,38 @@ impl Loader {
             has_vertex_array_object,
             shaders,
             images,
+            Rc::clone(logic_loader.language().unwrap()),
         ));
 
-        let logic = logic_loader.complete();
+        let mut logic = logic_loader.complete(rules);
+
+        logic.set_input_method(input_method);
+
+        if let Some(word_list) = word_list {
+            let report = logic.load_word_list_text(&word_list);
 
-        match GamePainter::new(paint_data) {
+            for rejected in report.rejected.iter() {
+                console::log_1(&format!(
+                    "Word list line {}: {}: {}",
+                    rejected.line_num,
+                    rejected.word,
+                    rejected.reason,
+                ).into());
+            }
+
+            if report.n_words == 0 {
+                show_error("The word list doesn’t contain any usable words");
+                return;
+            }
+        }
+
+        if let Some(saved_state) = saved_state {
+            if let Err(e) = logic.load_state(&saved_state) {
+                console::log_1(&format!("Error loading save: {}", e).into());
+            }
+        }
+
+        match GamePainter::new(paint_data, logic.n_teams()) {
             Ok(painter) => {
                 let _ = context.canvas.style().set_property("display", "block");
                 let diveno = Diveno::new(context, painter, sounds, logic);
@@ -601,19 +691,51 @@ impl Diveno {
 
         if had_event {
             self.update_next_sound();
+            self.save_game();
         }
 
         redraw_queued
     }
 
+    fn save_game(&self) {
+        let Ok(Some(storage)) = self.context.window.local_storage()
+        else {
+            return;
+        };
+
+        let state = self.logic.save_state();
+
+        if storage.set_item(SAVE_STORAGE_KEY, &state).is_err() {
+            console::log_1(&"Error saving the game".into());
+        }
+
+        let accepted_words = self.logic.accepted_words()
+            .iter()
+            .map(|word| word.to_lowercase() + "\n")
+            .collect::<String>();
+
+        if storage.set_item(
+            ACCEPTED_WORDS_STORAGE_KEY,
+            &accepted_words,
+        ).is_err() {
+            console::log_1(&"Error saving the accepted words".into());
+        }
+    }
+
     fn redraw(&mut self) -> Timeout {
+        self.logic.update_timers();
+
         let redraw_timeout = if self.flush_logic_events() {
             timeout::IMMEDIATELY
         } else {
             Timeout::Forever
         };
 
-        self.painter.paint(&mut self.logic).min(redraw_timeout)
+        // Make sure there will be another redraw when a timer runs
+        // out so that update_timers will be called
+        self.painter.paint(&mut self.logic)
+            .min(redraw_timeout)
+            .min(self.logic.timer_timeout())
     }
 
     fn queue_redraw(&mut self, delay: i64) {
@@ -704,6 +826,56 @@ impl Diveno {
     }
 
     fn handle_key_event(&mut self, event: web_sys::KeyboardEvent) {
+        if event.ctrl_key() {
+            let key = match event.key().as_str() {
+                "z" | "Z" if event.shift_key() => game::logic::Key::Redo,
+                "z" | "Z" => game::logic::Key::Undo,
+                "y" | "Y" => game::logic::Key::Redo,
+                "Enter" => game::logic::Key::ForceEnter,
+                "d" | "D" => game::logic::Key::NextDifficulty,
+                _ => return,
+            };
+
+            event.prevent_default();
+
+            self.logic.press_key(key);
+
+            if let game::logic::Key::NextDifficulty = key {
+                // Log the difficulty to the console so that the host
+                // knows which band was chosen
+                let message = match self.logic.word_difficulty() {
+                    Some(difficulty) => {
+                        format!("Next words will be {}", difficulty)
+                    },
+                    None => "Next words can be of any difficulty".to_string(),
+                };
+                console::log_1(&message.into());
+            }
+
+            if self.flush_logic_events() {
+                self.queue_redraw(0);
+            }
+
+            self.queue_timer_redraw();
+
+            return;
+        }
+
+        if event.key() == "F2" {
+            // Log the words that could still be the answer to the
+            // console so that the host can check them without the
+            // players seeing
+            let words = self.logic.possible_words();
+            console::log_1(&format!(
+                "{} possible dictionary words \
+                 (derived forms aren’t listed): {}",
+                words.len(),
+                words.join(", "),
+            ).into());
+            event.prevent_default();
+            return;
+        }
+
         let key = match event.key().as_str() {
             "Enter" => game::logic::Key::Enter,
             "Backspace" => game::logic::Key::Backspace,
@@ -711,6 +883,8 @@ impl Diveno {
             "PageDown" => game::logic::Key::PageDown,
             " " => game::logic::Key::Space,
             "Home" => game::logic::Key::Home,
+            "End" => game::logic::Key::End,
+            "Insert" => game::logic::Key::Insert,
             "Dead" => game::logic::Key::Dead,
             "ArrowLeft" => game::logic::Key::Left,
             "ArrowRight" => game::logic::Key::Right,
@@ -721,17 +895,17 @@ impl Diveno {
             s => {
                 let mut chars = s.chars();
 
-                let ch = chars.next().and_then(|ch| {
-                    if chars.next().is_none() && ch.is_alphabetic() {
-                        Some(ch)
-                    } else {
-                        None
-                    }
-                });
+                let Some(ch) = chars.next().filter(|_| chars.next().is_none())
+                else {
+                    return;
+                };
 
-                match ch {
-                    Some(ch) => game::logic::Key::Letter(ch),
-                    None => return,
+                if ch.is_alphabetic() {
+                    game::logic::Key::Letter(ch)
+                } else if let Some(digit) = ch.to_digit(10) {
+                    game::logic::Key::Digit(digit)
+                } else {
+                    return;
                 }
             },
         };
@@ -741,13 +915,89 @@ impl Diveno {
         if self.flush_logic_events() {
             self.queue_redraw(0);
         }
+
+        self.queue_timer_redraw();
+    }
+
+    // The timers are updated in redraw so this makes sure there will
+    // be a redraw when the next one runs out, even if the page with
+    // the timer isn’t visible
+    fn queue_timer_redraw(&mut self) {
+        if let Timeout::Milliseconds(ms) = self.logic.timer_timeout() {
+            self.queue_redraw(ms);
+        }
     }
 }
 
+fn load_saved_state() -> Option<String> {
+    web_sys::window()?
+        .local_storage().ok()??
+        .get_item(SAVE_STORAGE_KEY).ok()?
+}
+
 #[wasm_bindgen]
-pub fn init_diveno() {
+pub fn init_diveno(
+    n_teams: usize,
+    black_ball: String,
+    guess_time: u32,
+    pass_on_timeout: bool,
+    steal: bool,
+    auto_pass: bool,
+    hint_on_pass: bool,
+    difficulty: String,
+    input_method: String,
+    language: Option<String>,
+    resume: bool,
+    seed: Option<String>,
+    word_list: Option<String>,
+) {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
 
+    let black_ball = match black_ball.parse() {
+        Ok(black_ball) => black_ball,
+        Err(e) => {
+            show_error(&e);
+            return;
+        },
+    };
+
+    // An empty string means that words of any difficulty can be picked
+    let word_difficulty = if difficulty.is_empty() {
+        None
+    } else {
+        match difficulty.parse() {
+            Ok(difficulty) => Some(difficulty),
+            Err(e) => {
+                show_error(&e);
+                return;
+            },
+        }
+    };
+
+    let language = language.unwrap_or_else(|| {
+        game::language::DEFAULT_LANGUAGE.to_string()
+    });
+
+    let input_method = match input_method.parse() {
+        Ok(input_method) => input_method,
+        Err(e) => {
+            show_error(&e);
+            return;
+        },
+    };
+
+    let rules = Rules {
+        n_teams: n_teams.clamp(1, game::logic::MAX_TEAMS),
+        black_ball,
+        guess_time: (guess_time > 0).then_some(guess_time as i64 * 1000),
+        guess_time_up_rejects: pass_on_timeout,
+        steal,
+        auto_pass,
+        hint_on_pass,
+        word_difficulty,
+        ..Default::default()
+    };
+
     let context = match Context::new() {
         Ok(c) => c,
         Err(e) => {
@@ -756,7 +1006,30 @@ pub fn init_diveno() {
         }
     };
 
-    let loader = Loader::new(context);
+    let saved_state = if resume {
+        load_saved_state()
+    } else {
+        None
+    };
+
+    let seed = match seed.map(|seed| seed.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(_)) => {
+            show_error("Invalid seed");
+            return;
+        },
+        None => None,
+    };
+
+    let loader = Loader::new(
+        context,
+        rules,
+        input_method,
+        &language,
+        seed,
+        word_list,
+        saved_state,
+    );
 
     let floating_pointer = loader.start_floating();
 
@@ -14,69 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+// The dictionary and the word list format are shared with the game
+#[path = "../../src/game/dictionary.rs"]
+#[allow(dead_code)]
 mod dictionary;
+#[path = "../../src/game/word_list.rs"]
+#[allow(dead_code)]
+mod word_list;
 
 use std::process::ExitCode;
-use std::mem::size_of;
-use dictionary::Node;
-
-const BITS_PER_CHOICE: u32 = 5;
-
-fn append_word(buf: &mut String, dictionary: &[u8], mut word: u64) -> bool {
-    // Skip the root node
-    let Some(Node { remainder, child_offset, .. }) =
-        Node::extract(dictionary)
-    else {
-        return false;
-    };
-
-    if child_offset == 0 {
-        return false;
-    }
-
-    let mut data = &remainder[child_offset..];
-
-    loop {
-        let to_skip = word & ((1 << BITS_PER_CHOICE) - 1);
-        word >>= BITS_PER_CHOICE;
-
-        for _ in 0..to_skip {
-            let Some(node) = Node::extract(data)
-            else {
-                return false;
-            };
-
-            if node.sibling_offset == 0 {
-                return false;
-            }
-
-            data = match node.remainder.get(node.sibling_offset..) {
-                Some(d) => d,
-                None => return false,
-            };
-        }
-
-        let Some(node) = Node::extract(data)
-        else {
-            return false;
-        };
-
-        if node.letter == '\0' {
-            return true;
-        }
-
-        buf.push(node.letter);
-
-        if node.child_offset == 0 {
-            return false;
-        }
-
-        data = match node.remainder.get(node.child_offset..) {
-            Some(d) => d,
-            None => return false,
-        };
-    }
-}
+use dictionary::Dictionary;
 
 fn main() -> ExitCode {
     let mut args = std::env::args_os();
@@ -89,7 +36,7 @@ fn main() -> ExitCode {
     };
 
     let dictionary = match std::fs::read(&dictionary_filename) {
-        Ok(d) => d,
+        Ok(d) => Dictionary::new(d.into_boxed_slice()),
         Err(e) => {
             eprintln!("{}: {}", dictionary_filename.to_string_lossy(), e);
             return ExitCode::FAILURE;
@@ -104,23 +51,17 @@ fn main() -> ExitCode {
         },
     };
 
-    let mut buf = String::new();
-    let mut ret = ExitCode::SUCCESS;
-
-    for index in (0..word_list.len()).step_by(size_of::<u64>()) {
-        let mut bytes = [0u8; size_of::<u64>()];
-        bytes.copy_from_slice(&word_list[index..index + size_of::<u64>()]);
-        let word = u64::from_le_bytes(bytes);
-
-        buf.clear();
+    match word_list::decode(&dictionary, &word_list) {
+        Ok(words) => {
+            for word in words {
+                println!("{}", word);
+            }
 
-        if append_word(&mut buf, &dictionary, word) {
-            println!("{}", buf);
-        } else {
-            eprintln!("couldn’t decode word at {}", index);
-            ret = ExitCode::FAILURE;
-        }
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("{}: {}", word_list_filename.to_string_lossy(), e);
+            ExitCode::FAILURE
+        },
     }
-
-    ret
 }
@@ -14,121 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+// The dictionary and the word list format are shared with the game.
+// See src/game/word_list.rs for a description of the format. Each
+// word is written with the encoding that the dictionary gives for it
+// while iterating over the words.
+#[path = "../../src/game/dictionary.rs"]
+#[allow(dead_code)]
 mod dictionary;
+#[path = "../../src/game/word_list.rs"]
+#[allow(dead_code)]
+mod word_list;
 
 use std::process::ExitCode;
-use std::{fmt, io};
-use io::Write;
-use std::ffi::OsStr;
-use dictionary::Node;
-
-// The word list is stored as a list of u64’s. That way each word
-// takes up the same amount of space and it’s easy to index to a
-// random word. The bits of the u64 are split into 5-bit numbers. Each
-// number represents the number of siblings to skip while traversing
-// the dictionary graph before descending to a child. When the number
-// points to descending to a '\0' character in the dictionary the word
-// is finished. This means we can use a dictionary whose alphabet is
-// at most 32 letters and each word is at most 64/5=12 letters long.
-
-const BITS_PER_CHOICE: u32 = 5;
-
-enum CompressError {
-    TooManyBits,
-    TooManySkips,
-    NotInDictionary,
-    DictionaryCorrupt,
-}
-
-impl fmt::Display for CompressError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            CompressError::TooManyBits => write!(f, "Too many bits"),
-            CompressError::TooManySkips => write!(f, "Too many skips"),
-            CompressError::NotInDictionary => write!(f, "Not in dictionary"),
-            CompressError::DictionaryCorrupt => write!(f, "Dictionary corrupt"),
-        }
-    }
-}
-
-fn compress_word(dictionary: &[u8], word: &str) -> Result<u64, CompressError> {
-    let mut n_choices = 0;
-    let mut choices = 0u64;
-
-    // Skip the root node
-    let Some(Node { remainder, child_offset, .. }) =
-        Node::extract(dictionary)
-    else {
-        return Err(CompressError::DictionaryCorrupt);
-    };
-
-    if child_offset == 0 {
-        return Err(CompressError::DictionaryCorrupt);
-    }
-
-    let mut data = &remainder[child_offset..];
-    let mut word = word.chars().flat_map(|c| c.to_lowercase());
-    let mut next_letter = word.next();
-    let mut skip_count = 0;
-
-    loop {
-        let Some(node) = Node::extract(data)
-        else {
-            return Err(CompressError::DictionaryCorrupt);
-        };
-
-        if node.letter == next_letter.unwrap_or('\0') {
-            if (n_choices + 1) * BITS_PER_CHOICE > u64::BITS {
-                return Err(CompressError::TooManyBits);
-            } else {
-                choices |= skip_count << (n_choices * BITS_PER_CHOICE);
-                n_choices += 1;
-                skip_count = 0;
-            }
-
-            if next_letter.is_none() {
-                return Ok(choices);
-            }
-
-            if node.child_offset == 0 {
-                return Err(CompressError::NotInDictionary);
-            }
-
-            next_letter = word.next();
-
-            data = match node.remainder.get(node.child_offset..) {
-                Some(d) => d,
-                None => return Err(CompressError::DictionaryCorrupt),
-            };
-        } else {
-            if node.sibling_offset == 0 {
-                return Err(CompressError::NotInDictionary);
-            }
-
-            skip_count += 1;
-
-            if u64::BITS - skip_count.leading_zeros() > BITS_PER_CHOICE {
-                return Err(CompressError::TooManySkips);
-            }
-
-            data = match node.remainder.get(node.sibling_offset..) {
-                Some(d) => d,
-                None => return Err(CompressError::DictionaryCorrupt),
-            };
-        }
-    }
-}
-
-fn write_words(words: &[u64], output_filename: &OsStr) -> io::Result<()> {
-    let output = std::fs::File::create(output_filename)?;
-    let mut output = io::BufWriter::new(output);
-
-    for &word in words.iter() {
-        output.write(&word.to_le_bytes())?;
-    }
-
-    output.flush()
-}
+use std::io;
+use std::collections::HashMap;
+use dictionary::Dictionary;
 
 fn main() -> ExitCode {
     let mut args = std::env::args_os();
@@ -141,14 +41,18 @@ fn main() -> ExitCode {
     };
 
     let dictionary = match std::fs::read(&dictionary_filename) {
-        Ok(d) => d,
+        Ok(d) => Dictionary::new(d.into_boxed_slice()),
         Err(e) => {
             eprintln!("{}: {}", dictionary_filename.to_string_lossy(), e);
             return ExitCode::FAILURE;
         },
     };
 
-    let mut words = Vec::<u64>::new();
+    let encodings = dictionary.words()
+        .map(|found| (found.word, found.encoding))
+        .collect::<HashMap<String, Vec<u8>>>();
+
+    let mut words = Vec::<Vec<u8>>::new();
     let mut ret = ExitCode::SUCCESS;
 
     for line in io::stdin().lines() {
@@ -160,16 +64,26 @@ fn main() -> ExitCode {
             },
         };
 
-        match compress_word(&dictionary, &line) {
-            Ok(word) => words.push(word),
-            Err(e) => {
-                eprintln!("{}: {}", line, e);
+        // The game refuses the whole word list if it has a word that
+        // is too long
+        if line.chars().count() > word_list::MAX_WORD_LENGTH {
+            eprintln!("{}: Too long", line);
+            ret = ExitCode::FAILURE;
+            continue;
+        }
+
+        match encodings.get(&line.to_lowercase()) {
+            Some(encoding) => words.push(encoding.clone()),
+            None => {
+                eprintln!("{}: Not in dictionary", line);
                 ret = ExitCode::FAILURE;
             },
         }
     }
 
-    if let Err(e) = write_words(&words, &output_filename) {
+    let data = word_list::encode(words.iter().map(Vec::as_slice));
+
+    if let Err(e) = std::fs::write(&output_filename, data) {
         eprintln!("{}: {}", output_filename.to_string_lossy(), e);
         ret = ExitCode::FAILURE;
     }